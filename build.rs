@@ -2,57 +2,94 @@ use std::env;
 use std::path::PathBuf;
 use std::process::Command;
 
+/// Compute capabilities `solver.cu` is compiled to native cubins for. Kept in
+/// sync with `CUDA_ARCHES` in `src/cuda.rs`, which picks among these at
+/// runtime by reading `prop.major`/`prop.minor`. The lowest entry also
+/// supplies the virtual architecture for the PTX fallback (forward-JIT
+/// compatible with anything newer than the newest cubin here).
+const CUDA_ARCHES: &[&str] = &["70", "75", "80", "86", "89", "90"];
+
 fn main() {
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
-    
+
     let kernel_path = "kernels/solver.cu";
-    let ptx_path = out_dir.join("solver.ptx");
-    
+    let csr_transpose_kernel_path = "kernels/csr_transpose.cu";
+    let csr_transpose_ptx_path = out_dir.join("csr_transpose.ptx");
+
     println!("cargo:rerun-if-changed={}", kernel_path);
-    
-    // Detect GPU architecture or use environment variable, default to sm_89
-    // Note: sm_120 (Blackwell) requires CUDA 12.8+, so we cap at sm_89 for compatibility
-    let arch = env::var("CUDA_ARCH").unwrap_or_else(|_| {
-        if let Ok(output) = Command::new("nvidia-smi")
-            .args(["--query-gpu=compute_cap", "--format=csv,noheader"])
-            .output()
-        {
-            if output.status.success() {
-                let cap = String::from_utf8_lossy(&output.stdout);
-                let cap = cap.trim().lines().next().unwrap_or("8.9");
-                let major_minor: Vec<&str> = cap.split('.').collect();
-                if major_minor.len() == 2 {
-                    let major: u32 = major_minor[0].parse().unwrap_or(8);
-                    let minor: u32 = major_minor[1].parse().unwrap_or(9);
-                    // Cap at sm_89 (Ada) for CUDA 12.0-12.7 compatibility
-                    if major > 8 || (major == 8 && minor > 9) {
-                        return "sm_89".to_string();
-                    }
-                    return format!("sm_{}{}", major, minor);
-                }
-            }
+    println!("cargo:rerun-if-changed={}", csr_transpose_kernel_path);
+    println!("cargo:rerun-if-env-changed=CUDA_MAX_REGS");
+
+    // Register cap passed to nvcc's `-maxrregcount`. This is purely a
+    // compile-time nvcc flag, so it has to be baked in here rather than
+    // taken as a runtime CLI flag; `GpuContext` only gets to choose which
+    // arch's cubin to load, not how it was compiled.
+    let max_regs = env::var("CUDA_MAX_REGS").ok();
+
+    let mut nvcc_extra_args: Vec<String> = Vec::new();
+    if let Some(max_regs) = &max_regs {
+        println!("cargo:warning=Capping solver.cu register usage at {} via CUDA_MAX_REGS", max_regs);
+        nvcc_extra_args.push(format!("-maxrregcount={}", max_regs));
+    }
+
+    for arch in CUDA_ARCHES {
+        let cubin_path = out_dir.join(format!("solver_sm_{}.cubin", arch));
+        let status = Command::new("nvcc")
+            .args([
+                "-cubin",
+                &format!("-arch=sm_{}", arch),
+                "-O3",
+                "--use_fast_math",
+            ])
+            .args(&nvcc_extra_args)
+            .args(["-o", cubin_path.to_str().unwrap(), kernel_path])
+            .status()
+            .expect("Failed to run nvcc. Make sure CUDA toolkit is installed.");
+
+        if !status.success() {
+            panic!("nvcc failed to compile {} for sm_{}", kernel_path, arch);
         }
-        "sm_89".to_string()
-    });
-    
-    println!("cargo:warning=Compiling CUDA kernels for {}", arch);
-    
+    }
+
+    // PTX fallback at the lowest listed arch's virtual compute capability,
+    // so `GpuContext` can JIT it for any device newer than our newest cubin
+    // instead of failing outright.
+    let fallback_arch = CUDA_ARCHES.first().expect("CUDA_ARCHES must not be empty");
+    let ptx_path = out_dir.join("solver.ptx");
     let status = Command::new("nvcc")
         .args([
             "-ptx",
-            &format!("-arch={}", arch),
+            &format!("-arch=compute_{}", fallback_arch),
             "-O3",
             "--use_fast_math",
-            "-o",
-            ptx_path.to_str().unwrap(),
-            kernel_path,
         ])
+        .args(&nvcc_extra_args)
+        .args(["-o", ptx_path.to_str().unwrap(), kernel_path])
         .status()
         .expect("Failed to run nvcc. Make sure CUDA toolkit is installed.");
-    
+
     if !status.success() {
-        panic!("nvcc failed to compile {}", kernel_path);
+        panic!("nvcc failed to compile PTX fallback for {}", kernel_path);
     }
-    
+
+    if env::var_os("CARGO_FEATURE_GPU_CSR_TRANSPOSE").is_some() {
+        let status = Command::new("nvcc")
+            .args([
+                "-ptx",
+                &format!("-arch=compute_{}", fallback_arch),
+                "-O3",
+                "--use_fast_math",
+                "-o",
+                csr_transpose_ptx_path.to_str().unwrap(),
+                csr_transpose_kernel_path,
+            ])
+            .status()
+            .expect("Failed to run nvcc. Make sure CUDA toolkit is installed.");
+
+        if !status.success() {
+            panic!("nvcc failed to compile {}", csr_transpose_kernel_path);
+        }
+    }
+
     println!("cargo:rustc-env=OUT_DIR={}", out_dir.display());
 }
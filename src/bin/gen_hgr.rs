@@ -1,12 +1,28 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Parser;
-use std::fs::{self, File};
-use std::io::{BufWriter, Write};
+use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 
 use hg_bench::cuda::GpuContext;
+use hg_bench::hgr;
 use tig_challenges::hypergraph::{Challenge, Track};
 
+/// Extension to append to generated .hgr files so `run_sigma_freud` (and
+/// `hgr::read_hgr`) pick the matching decompressor back up by filename.
+fn compress_suffix(spec: &str) -> Result<&'static str> {
+    match spec.to_ascii_lowercase().as_str() {
+        "none" => Ok(""),
+        "zst" => Ok(".zst"),
+        "gz" => Ok(".gz"),
+        "bz2" => Ok(".bz2"),
+        other => Err(anyhow!(
+            "Unknown --compress '{}': expected none, zst, gz, or bz2",
+            other
+        )),
+    }
+}
+
 #[derive(Parser)]
 #[command(name = "gen_hgr")]
 #[command(about = "Generate TIG hypergraph instances and export as .hgr files")]
@@ -25,6 +41,12 @@ struct Cli {
     /// Starting seed/nonce (instances use seed, seed+1, seed+2, ...)
     #[arg(short, default_value = "0")]
     s: u64,
+
+    /// Compress generated .hgr files: none, zst, gz, or bz2. Appends the
+    /// matching extension, which `run_sigma_freud`/`hgr::read_hgr` use to
+    /// pick the decompressor back up.
+    #[arg(long, default_value = "none")]
+    compress: String,
 }
 
 fn main() -> Result<()> {
@@ -38,6 +60,7 @@ fn main() -> Result<()> {
     println!("Output directory: {}", cli.output_folder.display());
     println!();
 
+    let compress_suffix = compress_suffix(&cli.compress)?;
     let ctx = GpuContext::new()?;
 
     for i in 0..cli.n {
@@ -56,8 +79,11 @@ fn main() -> Result<()> {
             &ctx.prop,
         )?;
 
-        // Output format: <size>_<seed_hex>_<i>.hgr
-        let hgr_path = cli.output_folder.join(format!("{}_{}_{}.hgr", cli.size, seed_hex, i));
+        // Output format: <size>_<seed_hex>_<i>.hgr[.zst|.gz|.bz2]
+        let hgr_path = cli.output_folder.join(format!(
+            "{}_{}_{}.hgr{}",
+            cli.size, seed_hex, i, compress_suffix
+        ));
         export_challenge_to_hgr(&challenge, &hgr_path, &ctx)?;
 
         println!(
@@ -102,23 +128,11 @@ fn export_challenge_to_hgr(
     let hyperedge_offsets: Vec<i32> = ctx.stream.memcpy_dtov(&challenge.d_hyperedge_offsets)?;
     let hyperedge_nodes: Vec<i32> = ctx.stream.memcpy_dtov(&challenge.d_hyperedge_nodes)?;
 
-    let file = File::create(path)?;
-    let mut writer = BufWriter::new(file);
-
-    writeln!(writer, "{} {}", challenge.num_hyperedges, challenge.num_nodes)?;
-
-    for i in 0..challenge.num_hyperedges as usize {
-        let start = hyperedge_offsets[i] as usize;
-        let end = hyperedge_offsets[i + 1] as usize;
-
-        let nodes: Vec<String> = hyperedge_nodes[start..end]
-            .iter()
-            .map(|&n| (n + 1).to_string())
-            .collect();
-
-        writeln!(writer, "{}", nodes.join(" "))?;
-    }
-
-    writer.flush()?;
-    Ok(())
+    hgr::write_hgr_edges(
+        path,
+        challenge.num_hyperedges,
+        challenge.num_nodes,
+        &hyperedge_offsets,
+        &hyperedge_nodes,
+    )
 }
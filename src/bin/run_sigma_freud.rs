@@ -1,11 +1,109 @@
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
 use std::time::Instant;
 
 use hg_bench::hgr;
+use hg_bench::scheduler::GpuPool;
+use hg_bench::solver;
+
+/// Best-so-far result for one instance, persisted to `manifest.json` in the
+/// output folder so repeated sweeps with different `-r`/`-e` settings
+/// monotonically accumulate the best partitions instead of clobbering a
+/// better prior result with a worse one.
+#[derive(Serialize, Deserialize, Clone)]
+struct ManifestEntry {
+    connectivity: u32,
+    time: f64,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct Manifest {
+    #[serde(flatten)]
+    instances: BTreeMap<String, ManifestEntry>,
+}
+
+fn load_manifest(path: &Path) -> Manifest {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(path: &Path, manifest: &Manifest) -> Result<()> {
+    let json = serde_json::to_string_pretty(manifest)?;
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Instance name for a discovered `.hgr` path, with both a trailing
+/// compression suffix (if any) and the `.hgr` extension stripped -- e.g.
+/// `corpus/200000_ab12_0.hgr.zst` -> `200000_ab12_0`. `None` for anything
+/// that isn't a (possibly compressed) `.hgr` file, so this also doubles as
+/// the discovery filter's predicate.
+fn instance_stem(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let without_compression = name
+        .strip_suffix(".zst")
+        .or_else(|| name.strip_suffix(".gz"))
+        .or_else(|| name.strip_suffix(".bz2"))
+        .unwrap_or(name);
+    without_compression.strip_suffix(".hgr").map(str::to_string)
+}
+
+/// Whether a freshly-solved `connectivity` should replace `best` in the
+/// manifest. Strictly-better only (`<`, not `<=`) -- a tie keeps the prior
+/// entry's partition/timing files on disk rather than rewriting them for no
+/// gain, and `None` (first time seeing this instance) always counts as an
+/// improvement.
+fn is_improvement(best: Option<&ManifestEntry>, connectivity: u32) -> bool {
+    best.map_or(true, |b| connectivity < b.connectivity)
+}
+
+/// Cap on .hgr files being read and parsed at once, so a folder of 200k-edge
+/// instances doesn't all land in host memory while the GPUs work through a
+/// backlog. Also bounds how many reader threads are spawned.
+const MAX_CONCURRENT_IO: usize = 4;
+
+/// Spawn a small pool of reader threads that pull the next unread path off
+/// `paths`, parse it with `hgr::read_hgr`, and push the result onto a
+/// bounded channel. This overlaps disk I/O and parsing with whatever the
+/// GPU pool is doing with previously prefetched instances, instead of the
+/// main thread blocking on `read_hgr` before every solve.
+fn spawn_prefetch(
+    paths: Vec<PathBuf>,
+) -> mpsc::Receiver<(usize, Result<(PathBuf, hgr::Hypergraph)>)> {
+    let (tx, rx) = mpsc::sync_channel(MAX_CONCURRENT_IO);
+    let paths = Arc::new(paths);
+    let next_index = Arc::new(AtomicUsize::new(0));
+    let num_readers = MAX_CONCURRENT_IO.min(paths.len()).max(1);
+
+    for _ in 0..num_readers {
+        let tx = tx.clone();
+        let paths = Arc::clone(&paths);
+        let next_index = Arc::clone(&next_index);
+        thread::spawn(move || loop {
+            let index = next_index.fetch_add(1, Ordering::SeqCst);
+            if index >= paths.len() {
+                break;
+            }
+            let path = paths[index].clone();
+            let parsed = hgr::read_hgr(&path).map(|hg| (path, hg));
+            if tx.send((index, parsed)).is_err() {
+                break;
+            }
+        });
+    }
+
+    rx
+}
 
 #[derive(Parser)]
 #[command(name = "run_sigma_freud")]
@@ -29,6 +127,33 @@ struct Cli {
     /// Refinement rounds
     #[arg(short, default_value = "2000")]
     r: u32,
+
+    /// CUDA devices to spread instances across: "all" or a comma-separated
+    /// list of ordinals (e.g. "0,1,3"). Defaults to every visible device.
+    #[arg(long, default_value = "all")]
+    gpus: String,
+
+    /// Re-solve instances that already have a .partition file, instead of
+    /// skipping them. A forced re-solve still only overwrites the stored
+    /// result if it actually improves on the manifest's best connectivity.
+    #[arg(long, default_value_t = false)]
+    force: bool,
+}
+
+struct SolvedInstance {
+    filename: String,
+    connectivity: u32,
+    elapsed: f64,
+    device: usize,
+    wrote: bool,
+}
+
+fn parse_devices(spec: &str) -> Result<Option<Vec<usize>>> {
+    if spec.eq_ignore_ascii_case("all") {
+        return Ok(None);
+    }
+    let devices: Result<Vec<usize>, _> = spec.split(',').map(|s| s.trim().parse::<usize>()).collect();
+    Ok(Some(devices.map_err(|e| anyhow!("Invalid --gpus list '{}': {}", spec, e))?))
 }
 
 fn main() -> Result<()> {
@@ -39,7 +164,7 @@ fn main() -> Result<()> {
     let mut hgr_files: Vec<PathBuf> = fs::read_dir(&cli.hgr_folder)?
         .filter_map(|e| e.ok())
         .map(|e| e.path())
-        .filter(|p| p.extension().map_or(false, |ext| ext == "hgr"))
+        .filter(|p| instance_stem(p).is_some())
         .collect();
 
     hgr_files.sort();
@@ -48,50 +173,157 @@ fn main() -> Result<()> {
         return Err(anyhow!("No .hgr files found in {}", cli.hgr_folder.display()));
     }
 
-    println!("Found {} .hgr files in {}", hgr_files.len(), cli.hgr_folder.display());
+    let manifest_path = cli.output_folder.join("manifest.json");
+    let manifest = load_manifest(&manifest_path);
+
+    let pending: Vec<PathBuf> = hgr_files
+        .into_iter()
+        .filter(|p| {
+            let filename = instance_stem(p).unwrap();
+            let partition_path = cli.output_folder.join(format!("{}.partition", filename));
+            cli.force || !partition_path.exists()
+        })
+        .collect();
+
     println!("Output folder: {}", cli.output_folder.display());
     println!("Settings: k={}, epsilon={}, refinement={}", cli.k, cli.e, cli.r);
+
+    if pending.is_empty() {
+        println!("All instances already have a .partition file; nothing to do (use --force to re-solve).");
+        return Ok(());
+    }
+
+    let devices = parse_devices(&cli.gpus)?;
+    let pool = GpuPool::new(devices)?;
+
+    println!("{} instances to solve ({} already done)", pending.len(), manifest.instances.len());
+    println!("Devices: {}", pool.num_devices());
     println!();
 
-    let mut total_time = 0.0;
-    let mut total_connectivity = 0u32;
+    let k = cli.k;
+    let e = cli.e;
+    let r = cli.r;
+    let output_folder = cli.output_folder.clone();
+    let total = pending.len();
+    let item_rx = spawn_prefetch(pending);
+    let manifest = Arc::new(manifest);
+    let manifest_for_workers = Arc::clone(&manifest);
 
-    for (i, hgr_path) in hgr_files.iter().enumerate() {
-        let filename = hgr_path.file_stem().unwrap().to_string_lossy();
-        print!("[{}/{}] {}... ", i + 1, hgr_files.len(), filename);
-        std::io::stdout().flush()?;
+    let results = pool.run_from_receiver(item_rx, total, move |ctx, parsed| -> Result<SolvedInstance> {
+        let (hgr_path, hypergraph) = parsed?;
+        let filename = instance_stem(&hgr_path).unwrap();
 
-        let hypergraph = hgr::read_hgr(&hgr_path)?;
-        let max_part_size = ((hypergraph.num_nodes as f64 / cli.k as f64) * (1.0 + cli.e)).ceil() as u32;
+        let max_part_size = ((hypergraph.num_nodes as f64 / k as f64) * (1.0 + e)).ceil() as u32;
 
         let start = Instant::now();
-        let partition = hg_bench::solver::solve(&hypergraph, cli.k, max_part_size, 2, Some(cli.r))?;
+        let partition = solver::solve_with_ctx(&hypergraph, k, max_part_size, 2, Some(r), ctx)?;
         let elapsed = start.elapsed().as_secs_f64();
 
         let connectivity = hgr::compute_connectivity(&hypergraph, &partition);
 
-        // Output with same name but .partition extension
-        let partition_path = cli.output_folder.join(format!("{}.partition", filename));
-        hgr::write_partition(&partition_path, &partition)?;
+        let best = manifest_for_workers.instances.get(&filename);
+        let improved = is_improvement(best, connectivity);
+
+        let partition_path = output_folder.join(format!("{}.partition", filename));
+        // Resume mode can desync the manifest from disk (e.g. a `.partition`
+        // file removed by hand): write whenever it's missing, not only on
+        // strict improvement, so a manifest "best" never points at nothing.
+        let should_write = improved || !partition_path.exists();
 
-        // Write timing file
-        let timing_path = cli.output_folder.join(format!("{}.time", filename));
-        let mut timing_file = File::create(&timing_path)?;
-        writeln!(timing_file, "{:.3}", elapsed)?;
+        if should_write {
+            hgr::write_partition(&partition_path, &partition)?;
 
-        println!("KM1={}, time={:.2}s", connectivity, elapsed);
+            let timing_path = output_folder.join(format!("{}.time", filename));
+            let mut timing_file = File::create(&timing_path)?;
+            writeln!(timing_file, "{:.3}", elapsed)?;
+        }
 
-        total_time += elapsed;
-        total_connectivity += connectivity;
+        // Report whatever is actually on disk after this run: the fresh
+        // result when we just (re)wrote the partition file (whether because
+        // it improved on `best` or because the file had gone missing), the
+        // untouched manifest entry otherwise.
+        let (report_connectivity, report_elapsed) = if should_write {
+            (connectivity, elapsed)
+        } else {
+            let best = best.unwrap();
+            (best.connectivity, best.time)
+        };
+
+        Ok(SolvedInstance {
+            filename,
+            connectivity: report_connectivity,
+            elapsed: report_elapsed,
+            device: ctx.device,
+            wrote: should_write,
+        })
+    })?;
+
+    let mut manifest = Arc::try_unwrap(manifest).unwrap_or_else(|arc| (*arc).clone());
+    let mut total_time = 0.0;
+    let mut total_connectivity = 0u32;
+
+    for (i, result) in results.iter().enumerate() {
+        println!(
+            "[{}/{}] {}... KM1={}, time={:.2}s, device={}{}",
+            i + 1,
+            results.len(),
+            result.filename,
+            result.connectivity,
+            result.elapsed,
+            result.device,
+            if result.wrote { "" } else { " (kept prior best)" },
+        );
+        total_time += result.elapsed;
+        total_connectivity += result.connectivity;
+
+        manifest.instances.insert(
+            result.filename.clone(),
+            ManifestEntry {
+                connectivity: result.connectivity,
+                time: result.elapsed,
+            },
+        );
     }
 
+    save_manifest(&manifest_path, &manifest)?;
+
     println!();
     println!("=== Summary ===");
-    println!("Instances: {}", hgr_files.len());
+    println!("Instances: {}", results.len());
     println!("Total connectivity: {}", total_connectivity);
-    println!("Average connectivity: {:.1}", total_connectivity as f64 / hgr_files.len() as f64);
+    println!("Average connectivity: {:.1}", total_connectivity as f64 / results.len() as f64);
     println!("Total time: {:.2}s", total_time);
-    println!("Average time: {:.2}s", total_time / hgr_files.len() as f64);
+    println!("Average time: {:.2}s", total_time / results.len() as f64);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_prior_best_is_always_an_improvement() {
+        assert!(is_improvement(None, 100));
+        assert!(is_improvement(None, 0));
+    }
+
+    #[test]
+    fn strictly_lower_connectivity_is_an_improvement() {
+        let best = ManifestEntry {
+            connectivity: 50,
+            time: 1.0,
+        };
+        assert!(is_improvement(Some(&best), 49));
+    }
+
+    #[test]
+    fn equal_or_higher_connectivity_is_not_an_improvement() {
+        let best = ManifestEntry {
+            connectivity: 50,
+            time: 1.0,
+        };
+        assert!(!is_improvement(Some(&best), 50));
+        assert!(!is_improvement(Some(&best), 51));
+    }
+}
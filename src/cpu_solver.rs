@@ -0,0 +1,454 @@
+//! CPU reference partitioner: the `--device cpu` backend and the
+//! correctness oracle the CUDA tracks are checked against when no GPU is
+//! available (CI, laptops, `--device auto` with zero visible devices).
+//!
+//! Standard multilevel pipeline: heavy-edge coarsening down to a small
+//! hypergraph, greedy growth for the initial k-way partition, then FM-style
+//! single-node relocation passes to refine. `effort`/`refinement` govern the
+//! same knobs the GPU tracks expose (coarsening aggressiveness and pass
+//! count), so CPU and GPU runs are comparable apples to apples.
+
+use crate::hgr::Hypergraph;
+use crate::profile::Profiler;
+use anyhow::Result;
+use std::collections::VecDeque;
+
+/// One level of the coarsening hierarchy: the (possibly contracted)
+/// hypergraph at this level, plus the mapping from the next-finer level's
+/// node ids to this level's cluster ids (`None` for the finest level).
+struct Level {
+    hg: Hypergraph,
+    cluster_of_finer_node: Option<Vec<u32>>,
+}
+
+pub fn solve(
+    hypergraph: &Hypergraph,
+    k: u32,
+    max_part_size: u32,
+    effort: u32,
+    refinement: Option<u32>,
+) -> Result<Vec<u32>> {
+    solve_with_profiler(hypergraph, k, max_part_size, effort, refinement, None)
+}
+
+/// Same as [`solve`], but folds each multilevel phase's wall time into
+/// `profiler`'s cost centers ("cpu_coarsen", "cpu_initial_partition",
+/// "cpu_refine") when one is given. A no-op beyond the extra parameter when
+/// `profiler` is `None` or disabled.
+pub fn solve_with_profiler(
+    hypergraph: &Hypergraph,
+    k: u32,
+    max_part_size: u32,
+    effort: u32,
+    refinement: Option<u32>,
+    profiler: Option<&Profiler>,
+) -> Result<Vec<u32>> {
+    if hypergraph.num_nodes == 0 {
+        return Ok(Vec::new());
+    }
+
+    let refinement_rounds = refinement.unwrap_or_else(|| default_refinement_rounds(effort));
+
+    let levels = match profiler {
+        Some(p) => p.record("cpu_coarsen", || coarsen(hypergraph, k, effort)),
+        None => coarsen(hypergraph, k, effort),
+    };
+
+    let coarsest = &levels.last().unwrap().hg;
+    let mut partition = match profiler {
+        Some(p) => p.record("cpu_initial_partition", || initial_partition(coarsest, k, max_part_size)),
+        None => initial_partition(coarsest, k, max_part_size),
+    };
+    partition = match profiler {
+        Some(p) => p.record("cpu_refine", || refine(coarsest, partition, k, max_part_size, refinement_rounds)),
+        None => refine(coarsest, partition, k, max_part_size, refinement_rounds),
+    };
+
+    // Uncoarsen: project each level's partition onto the next-finer level
+    // and refine again, from coarsest back to the original hypergraph.
+    for level in levels.iter().rev().skip(1) {
+        let cluster_of_finer_node = level
+            .cluster_of_finer_node
+            .as_ref()
+            .expect("only the finest level has no cluster mapping");
+        partition = cluster_of_finer_node
+            .iter()
+            .map(|&cluster| partition[cluster as usize])
+            .collect();
+        partition = match profiler {
+            Some(p) => p.record("cpu_refine", || refine(&level.hg, partition, k, max_part_size, refinement_rounds)),
+            None => refine(&level.hg, partition, k, max_part_size, refinement_rounds),
+        };
+    }
+
+    Ok(partition)
+}
+
+fn default_refinement_rounds(effort: u32) -> u32 {
+    match effort {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        3 => 8,
+        4 => 16,
+        _ => 32,
+    }
+}
+
+/// Heavy-edge-style coarsening: repeatedly cluster each unmatched node with
+/// whichever unmatched neighbor shares the most total hyperedge weight,
+/// until the graph shrinks below a small multiple of `k` or stops shrinking.
+fn coarsen(hypergraph: &Hypergraph, k: u32, effort: u32) -> Vec<Level> {
+    let min_coarsest_nodes = (4 * k).max(1) as usize;
+    let max_levels = 10 + effort as usize * 4;
+
+    let mut levels = vec![Level {
+        hg: clone_hypergraph(hypergraph),
+        cluster_of_finer_node: None,
+    }];
+
+    for _ in 0..max_levels {
+        let current = &levels.last().unwrap().hg;
+        if current.num_nodes as usize <= min_coarsest_nodes {
+            break;
+        }
+
+        let (clusters, num_clusters) = heavy_edge_matching(current);
+        if num_clusters as usize >= current.num_nodes as usize {
+            break; // no merges happened; further coarsening wouldn't help
+        }
+
+        let contracted = contract(current, &clusters, num_clusters);
+        levels.push(Level {
+            hg: contracted,
+            cluster_of_finer_node: Some(clusters),
+        });
+    }
+
+    levels
+}
+
+/// Greedily pairs each node with the unmatched neighbor it shares the most
+/// hyperedge weight with. Returns (cluster id per node, number of clusters).
+fn heavy_edge_matching(hg: &Hypergraph) -> (Vec<u32>, u32) {
+    let num_nodes = hg.num_nodes as usize;
+    let mut cluster_of = vec![u32::MAX; num_nodes];
+    let mut next_cluster = 0u32;
+
+    for node in 0..num_nodes {
+        if cluster_of[node] != u32::MAX {
+            continue;
+        }
+
+        let mut affinity: std::collections::HashMap<u32, i64> = std::collections::HashMap::new();
+        let start = hg.node_offsets[node] as usize;
+        let end = hg.node_offsets[node + 1] as usize;
+        for &hedge in &hg.node_hyperedges[start..end] {
+            let hedge = hedge as usize;
+            let weight = hg.hyperedge_weights.get(hedge).copied().unwrap_or(1) as i64;
+            let he_start = hg.hyperedge_offsets[hedge] as usize;
+            let he_end = hg.hyperedge_offsets[hedge + 1] as usize;
+            for &neighbor in &hg.hyperedge_nodes[he_start..he_end] {
+                let neighbor = neighbor as usize;
+                if neighbor != node && neighbor < num_nodes && cluster_of[neighbor] == u32::MAX {
+                    *affinity.entry(neighbor as u32).or_insert(0) += weight;
+                }
+            }
+        }
+
+        let best_match = affinity.into_iter().max_by_key(|&(_, w)| w).map(|(n, _)| n);
+
+        cluster_of[node] = next_cluster;
+        if let Some(partner) = best_match {
+            cluster_of[partner as usize] = next_cluster;
+        }
+        next_cluster += 1;
+    }
+
+    (cluster_of, next_cluster)
+}
+
+/// Builds the contracted hypergraph: nodes become clusters (node weight =
+/// sum of the original member weights), hyperedges keep their identity and
+/// weight but have their pins remapped to clusters (with duplicates, from
+/// two pins landing in the same cluster, collapsed).
+fn contract(hg: &Hypergraph, cluster_of: &[u32], num_clusters: u32) -> Hypergraph {
+    let mut node_weights = vec![0i32; num_clusters as usize];
+    for (node, &cluster) in cluster_of.iter().enumerate() {
+        node_weights[cluster as usize] += hg.node_weights.get(node).copied().unwrap_or(1);
+    }
+
+    let mut hyperedge_offsets = Vec::with_capacity(hg.hyperedge_offsets.len());
+    let mut hyperedge_nodes = Vec::new();
+    hyperedge_offsets.push(0);
+
+    for hedge in 0..hg.num_hyperedges as usize {
+        let start = hg.hyperedge_offsets[hedge] as usize;
+        let end = hg.hyperedge_offsets[hedge + 1] as usize;
+
+        let mut seen: std::collections::HashSet<u32> = std::collections::HashSet::new();
+        for &node in &hg.hyperedge_nodes[start..end] {
+            if (node as usize) < cluster_of.len() {
+                seen.insert(cluster_of[node as usize]);
+            }
+        }
+        hyperedge_nodes.extend(seen.into_iter());
+        hyperedge_offsets.push(hyperedge_nodes.len() as i32);
+    }
+
+    let (node_offsets, node_hyperedges) =
+        crate::hgr::build_node_to_hyperedge(num_clusters as usize, &hyperedge_offsets, &hyperedge_nodes);
+
+    Hypergraph {
+        num_nodes: num_clusters,
+        num_hyperedges: hg.num_hyperedges,
+        hyperedge_offsets,
+        hyperedge_nodes,
+        node_offsets,
+        node_hyperedges,
+        hyperedge_weights: hg.hyperedge_weights.clone(),
+        node_weights,
+    }
+}
+
+fn clone_hypergraph(hg: &Hypergraph) -> Hypergraph {
+    Hypergraph {
+        num_nodes: hg.num_nodes,
+        num_hyperedges: hg.num_hyperedges,
+        hyperedge_offsets: hg.hyperedge_offsets.clone(),
+        hyperedge_nodes: hg.hyperedge_nodes.clone(),
+        node_offsets: hg.node_offsets.clone(),
+        node_hyperedges: hg.node_hyperedges.clone(),
+        hyperedge_weights: hg.hyperedge_weights.clone(),
+        node_weights: hg.node_weights.clone(),
+    }
+}
+
+/// Greedy-growth initial k-way partition: seed each part with a distinct
+/// high-degree node, then grow every part outward via its hyperedge
+/// neighborhood (a BFS-like frontier), respecting `max_part_size` except
+/// when adding to an empty part. Nodes no frontier can take go to the
+/// currently lightest part, which may push it past `max_part_size`.
+fn initial_partition(hg: &Hypergraph, k: u32, max_part_size: u32) -> Vec<u32> {
+    let num_nodes = hg.num_nodes as usize;
+    let k = k as usize;
+    let mut partition = vec![u32::MAX; num_nodes];
+    let mut part_weight = vec![0u32; k];
+
+    let mut by_degree: Vec<usize> = (0..num_nodes).collect();
+    by_degree.sort_by_key(|&n| std::cmp::Reverse(hg.node_offsets[n + 1] - hg.node_offsets[n]));
+
+    let mut frontiers: Vec<VecDeque<usize>> = vec![VecDeque::new(); k];
+    for (part, &seed) in by_degree.iter().take(k).enumerate() {
+        assign(hg, &mut partition, &mut part_weight, seed, part as u32);
+        frontiers[part].push_back(seed);
+    }
+
+    let mut next_unassigned = 0usize;
+    let mut assigned_count = k.min(num_nodes);
+
+    while assigned_count < num_nodes {
+        let mut progressed = false;
+
+        for part in 0..k {
+            while let Some(&node) = frontiers[part].front() {
+                if partition[node] != u32::MAX && partition[node] as usize != part {
+                    frontiers[part].pop_front();
+                    continue;
+                }
+                break;
+            }
+
+            let Some(&node) = frontiers[part].front() else { continue };
+            frontiers[part].pop_front();
+
+            for neighbor in hyperedge_neighbors(hg, node) {
+                if partition[neighbor] == u32::MAX {
+                    let weight = hg.node_weights.get(neighbor).copied().unwrap_or(1).max(0) as u32;
+                    if part_weight[part] + weight <= max_part_size || part_weight[part] == 0 {
+                        assign(hg, &mut partition, &mut part_weight, neighbor, part as u32);
+                        frontiers[part].push_back(neighbor);
+                        assigned_count += 1;
+                        progressed = true;
+                    }
+                }
+            }
+        }
+
+        if !progressed {
+            while next_unassigned < num_nodes && partition[next_unassigned] != u32::MAX {
+                next_unassigned += 1;
+            }
+            if next_unassigned >= num_nodes {
+                break;
+            }
+            let lightest_part = (0..k).min_by_key(|&p| part_weight[p]).unwrap();
+            assign(hg, &mut partition, &mut part_weight, next_unassigned, lightest_part as u32);
+            frontiers[lightest_part].push_back(next_unassigned);
+            assigned_count += 1;
+        }
+    }
+
+    partition
+}
+
+fn assign(hg: &Hypergraph, partition: &mut [u32], part_weight: &mut [u32], node: usize, part: u32) {
+    partition[node] = part;
+    part_weight[part as usize] += hg.node_weights.get(node).copied().unwrap_or(1).max(0) as u32;
+}
+
+fn hyperedge_neighbors(hg: &Hypergraph, node: usize) -> Vec<usize> {
+    let mut neighbors = Vec::new();
+    let start = hg.node_offsets[node] as usize;
+    let end = hg.node_offsets[node + 1] as usize;
+    for &hedge in &hg.node_hyperedges[start..end] {
+        let hedge = hedge as usize;
+        let he_start = hg.hyperedge_offsets[hedge] as usize;
+        let he_end = hg.hyperedge_offsets[hedge + 1] as usize;
+        for &n in &hg.hyperedge_nodes[he_start..he_end] {
+            let n = n as usize;
+            if n != node {
+                neighbors.push(n);
+            }
+        }
+    }
+    neighbors
+}
+
+/// FM-style refinement: for `rounds` passes over every node, move it to the
+/// neighboring part that most reduces weighted connectivity, subject to
+/// `max_part_size`. Stops early once a full pass makes no move.
+fn refine(hg: &Hypergraph, mut partition: Vec<u32>, k: u32, max_part_size: u32, rounds: u32) -> Vec<u32> {
+    let k = k as usize;
+    let mut part_weight = vec![0u32; k];
+    for (node, &part) in partition.iter().enumerate() {
+        part_weight[part as usize] += hg.node_weights.get(node).copied().unwrap_or(1).max(0) as u32;
+    }
+
+    for _ in 0..rounds {
+        let mut moved = false;
+
+        for node in 0..hg.num_nodes as usize {
+            let current_part = partition[node];
+            let node_weight = hg.node_weights.get(node).copied().unwrap_or(1).max(0) as u32;
+
+            let start = hg.node_offsets[node] as usize;
+            let end = hg.node_offsets[node + 1] as usize;
+            let incident: Vec<usize> = hg.node_hyperedges[start..end].iter().map(|&h| h as usize).collect();
+
+            let mut best_part = current_part;
+            let mut best_delta = 0i64;
+
+            for candidate in 0..k as u32 {
+                if candidate == current_part {
+                    continue;
+                }
+                if part_weight[candidate as usize] + node_weight > max_part_size {
+                    continue;
+                }
+
+                let mut delta = 0i64;
+                for &hedge in &incident {
+                    let weight = hg.hyperedge_weights.get(hedge).copied().unwrap_or(1) as i64;
+                    let he_start = hg.hyperedge_offsets[hedge] as usize;
+                    let he_end = hg.hyperedge_offsets[hedge + 1] as usize;
+
+                    let mut parts_now: std::collections::HashSet<u32> = std::collections::HashSet::new();
+                    let mut parts_after: std::collections::HashSet<u32> = std::collections::HashSet::new();
+                    for &pin in &hg.hyperedge_nodes[he_start..he_end] {
+                        let pin = pin as usize;
+                        let pin_part = if pin == node { current_part } else { partition[pin] };
+                        parts_now.insert(pin_part);
+                        let pin_part_after = if pin == node { candidate } else { partition[pin] };
+                        parts_after.insert(pin_part_after);
+                    }
+
+                    let cost_now = parts_now.len().saturating_sub(1) as i64;
+                    let cost_after = parts_after.len().saturating_sub(1) as i64;
+                    delta += (cost_after - cost_now) * weight;
+                }
+
+                if delta < best_delta {
+                    best_delta = delta;
+                    best_part = candidate;
+                }
+            }
+
+            if best_part != current_part {
+                part_weight[current_part as usize] -= node_weight;
+                part_weight[best_part as usize] += node_weight;
+                partition[node] = best_part;
+                moved = true;
+            }
+        }
+
+        if !moved {
+            break;
+        }
+    }
+
+    partition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hgr::build_node_to_hyperedge;
+
+    /// 8 nodes, 4 hyperedges, all unit weight -- small enough that `coarsen`
+    /// stops at the finest level (`4 * k == num_nodes`), so this exercises
+    /// `initial_partition`/`refine` directly without GPU access.
+    fn sample_hypergraph() -> Hypergraph {
+        let hyperedge_offsets = vec![0, 2, 5, 7, 8];
+        let hyperedge_nodes = vec![0, 1, 1, 2, 3, 4, 5, 6];
+        let (node_offsets, node_hyperedges) =
+            build_node_to_hyperedge(8, &hyperedge_offsets, &hyperedge_nodes);
+        Hypergraph {
+            num_nodes: 8,
+            num_hyperedges: 4,
+            hyperedge_offsets,
+            hyperedge_nodes,
+            node_offsets,
+            node_hyperedges,
+            hyperedge_weights: vec![1; 4],
+            node_weights: vec![1; 8],
+        }
+    }
+
+    #[test]
+    fn solve_assigns_every_node_to_a_valid_part() {
+        let hg = sample_hypergraph();
+        let max_part_size = crate::hgr::max_part_size(&hg.node_weights, 2, 0.5);
+        let partition = solve(&hg, 2, max_part_size, 0, Some(1)).unwrap();
+
+        assert_eq!(partition.len(), hg.num_nodes as usize);
+        assert!(partition.iter().all(|&p| p < 2));
+    }
+
+    #[test]
+    fn solve_respects_the_weight_budget_on_an_easy_instance() {
+        let hg = sample_hypergraph();
+        let max_part_size = crate::hgr::max_part_size(&hg.node_weights, 2, 0.5);
+        let partition = solve(&hg, 2, max_part_size, 0, Some(1)).unwrap();
+
+        let (feasible, _, _) =
+            crate::hgr::check_feasibility(&partition, &hg.node_weights, 2, max_part_size);
+        assert!(feasible);
+    }
+
+    #[test]
+    fn solve_on_empty_hypergraph_returns_empty_partition() {
+        let hg = Hypergraph {
+            num_nodes: 0,
+            num_hyperedges: 0,
+            hyperedge_offsets: vec![0],
+            hyperedge_nodes: vec![],
+            node_offsets: vec![0],
+            node_hyperedges: vec![],
+            hyperedge_weights: vec![],
+            node_weights: vec![],
+        };
+        let partition = solve(&hg, 2, 10, 0, Some(1)).unwrap();
+        assert!(partition.is_empty());
+    }
+}
@@ -1,39 +1,110 @@
 use anyhow::{anyhow, Result};
-use cudarc::driver::{CudaContext, CudaModule, CudaStream};
+use cudarc::driver::{CudaContext, CudaModule, CudaStream, LaunchConfig};
 use cudarc::nvrtc::Ptx;
 use cudarc::runtime::result::device::get_device_prop;
 use cudarc::runtime::sys::cudaDeviceProp;
 use std::sync::Arc;
 
+/// Compute capabilities `build.rs` bakes a native cubin for. Kept in sync
+/// with `CUDA_ARCHES` there; `best_cubin` picks the highest entry that's
+/// `<= ` the running device's capability within the same major version
+/// (cubins are binary-compatible forward within a major version, not
+/// across one), falling back to JIT-compiling the PTX fallback otherwise.
+const CUDA_ARCHES: &[(u32, u32)] = &[(7, 0), (7, 5), (8, 0), (8, 6), (8, 9), (9, 0)];
+
+const CUBIN_SM70: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/solver_sm_70.cubin"));
+const CUBIN_SM75: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/solver_sm_75.cubin"));
+const CUBIN_SM80: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/solver_sm_80.cubin"));
+const CUBIN_SM86: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/solver_sm_86.cubin"));
+const CUBIN_SM89: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/solver_sm_89.cubin"));
+const CUBIN_SM90: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/solver_sm_90.cubin"));
+
+fn cubin_for_arch(major: u32, minor: u32) -> Option<&'static [u8]> {
+    match (major, minor) {
+        (7, 0) => Some(CUBIN_SM70),
+        (7, 5) => Some(CUBIN_SM75),
+        (8, 0) => Some(CUBIN_SM80),
+        (8, 6) => Some(CUBIN_SM86),
+        (8, 9) => Some(CUBIN_SM89),
+        (9, 0) => Some(CUBIN_SM90),
+        _ => None,
+    }
+}
+
+fn best_cubin(major: u32, minor: u32) -> Option<(&'static [u8], (u32, u32))> {
+    CUDA_ARCHES
+        .iter()
+        .filter(|&&(maj, min)| maj == major && min <= minor)
+        .max_by_key(|&&(_, min)| min)
+        .and_then(|&arch| cubin_for_arch(arch.0, arch.1).map(|bytes| (bytes, arch)))
+}
+
 pub struct GpuContext {
     #[allow(dead_code)]
     pub ctx: Arc<CudaContext>,
     pub stream: Arc<CudaStream>,
     pub module: Arc<CudaModule>,
     pub prop: cudaDeviceProp,
+    /// Device ordinal this context is bound to.
+    pub device: usize,
 }
 
 impl GpuContext {
     pub fn new() -> Result<Self> {
+        Self::for_device(0)
+    }
+
+    /// Build a context bound to a specific CUDA device ordinal, so callers
+    /// that want one context per GPU (see `scheduler`) can construct a pool.
+    pub fn for_device(device: usize) -> Result<Self> {
         let num_gpus = CudaContext::device_count().map_err(|e| anyhow!("Failed to get device count: {:?}", e))?;
         if num_gpus == 0 {
             return Err(anyhow!("No CUDA devices found"));
         }
-        
-        let ctx = CudaContext::new(0).map_err(|e| anyhow!("Failed to create CUDA context: {:?}", e))?;
-        
-        let ptx_bytes = include_bytes!(concat!(env!("OUT_DIR"), "/solver.ptx"));
-        let ptx = Ptx::from_src(std::str::from_utf8(ptx_bytes).map_err(|e| anyhow!("Invalid PTX: {}", e))?);
-        
-        let module = ctx.load_module(ptx).map_err(|e| anyhow!("Failed to load PTX module: {:?}", e))?;
+        if device >= num_gpus as usize {
+            return Err(anyhow!("Requested device {} but only {} are visible", device, num_gpus));
+        }
+
+        let ctx = CudaContext::new(device).map_err(|e| anyhow!("Failed to create CUDA context: {:?}", e))?;
+        let prop = get_device_prop(device).map_err(|e| anyhow!("Failed to get device properties: {:?}", e))?;
+
+        let module = match best_cubin(prop.major as u32, prop.minor as u32) {
+            Some((cubin, arch)) => ctx
+                .load_module(Ptx::Image(cubin.to_vec()))
+                .map_err(|e| anyhow!("Failed to load prebuilt sm_{}{} cubin: {:?}", arch.0, arch.1, e))?,
+            None => {
+                let ptx_bytes = include_bytes!(concat!(env!("OUT_DIR"), "/solver.ptx"));
+                let ptx = Ptx::from_src(std::str::from_utf8(ptx_bytes).map_err(|e| anyhow!("Invalid PTX: {}", e))?);
+                ctx.load_module(ptx).map_err(|e| anyhow!("Failed to JIT-compile PTX fallback: {:?}", e))?
+            }
+        };
+
         let stream = ctx.default_stream();
-        let prop = get_device_prop(0).map_err(|e| anyhow!("Failed to get device properties: {:?}", e))?;
-        
+
         Ok(Self {
             ctx,
             stream,
             module,
             prop,
+            device,
         })
     }
+
+    /// Number of CUDA devices visible to the process.
+    pub fn device_count() -> Result<usize> {
+        Ok(CudaContext::device_count().map_err(|e| anyhow!("Failed to get device count: {:?}", e))? as usize)
+    }
+}
+
+/// `LaunchConfig` for a 1-D grid of `num_elems` work items at a fixed 256
+/// threads per block, for callers (e.g. `hgr::build_node_to_hyperedge_gpu`)
+/// that launch their own kernels outside a `GpuContext` method.
+pub(crate) fn launch_config_1d(num_elems: u32) -> LaunchConfig {
+    const THREADS_PER_BLOCK: u32 = 256;
+    let blocks = (num_elems + THREADS_PER_BLOCK - 1) / THREADS_PER_BLOCK;
+    LaunchConfig {
+        grid_dim: (blocks.max(1), 1, 1),
+        block_dim: (THREADS_PER_BLOCK, 1, 1),
+        shared_mem_bytes: 0,
+    }
 }
@@ -4,6 +4,194 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::Path;
 
+/// Compression scheme selected from a path's trailing extension, e.g.
+/// `corpus.hgr.zst` -> `Zstd`, `corpus.hgr.gz` -> `Gzip`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Zstd,
+    Gzip,
+    Bzip2,
+}
+
+fn detect_compression(path: &Path) -> Compression {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("zst") => Compression::Zstd,
+        Some("gz") => Compression::Gzip,
+        Some("bz2") => Compression::Bzip2,
+        _ => Compression::None,
+    }
+}
+
+/// Open `path` for reading, transparently decompressing based on its extension.
+fn open_reader(path: &Path) -> Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+
+    match detect_compression(path) {
+        Compression::None => Ok(Box::new(reader)),
+        Compression::Zstd => {
+            #[cfg(feature = "compress-zstd")]
+            {
+                Ok(Box::new(BufReader::new(zstd::Decoder::new(reader)?)))
+            }
+            #[cfg(not(feature = "compress-zstd"))]
+            {
+                Err(anyhow!(
+                    "{} is zstd-compressed but the `compress-zstd` feature is not enabled",
+                    path.display()
+                ))
+            }
+        }
+        Compression::Gzip => {
+            #[cfg(feature = "compress-gzip")]
+            {
+                Ok(Box::new(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader))))
+            }
+            #[cfg(not(feature = "compress-gzip"))]
+            {
+                Err(anyhow!(
+                    "{} is gzip-compressed but the `compress-gzip` feature is not enabled",
+                    path.display()
+                ))
+            }
+        }
+        Compression::Bzip2 => {
+            #[cfg(feature = "compress-bzip2")]
+            {
+                Ok(Box::new(BufReader::new(bzip2::bufread::BzDecoder::new(reader))))
+            }
+            #[cfg(not(feature = "compress-bzip2"))]
+            {
+                Err(anyhow!(
+                    "{} is bzip2-compressed but the `compress-bzip2` feature is not enabled",
+                    path.display()
+                ))
+            }
+        }
+    }
+}
+
+/// A writer that transparently compresses based on the target path's extension.
+/// Must be finished with [`CompressedWriter::finish`] so the trailing frame/footer
+/// (zstd epilogue, gzip CRC, bzip2 stream end) actually gets flushed to disk.
+enum CompressedWriter {
+    None(BufWriter<File>),
+    #[cfg(feature = "compress-zstd")]
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+    #[cfg(feature = "compress-gzip")]
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+    #[cfg(feature = "compress-bzip2")]
+    Bzip2(bzip2::write::BzEncoder<BufWriter<File>>),
+}
+
+impl CompressedWriter {
+    fn finish(self) -> Result<()> {
+        match self {
+            CompressedWriter::None(mut w) => {
+                w.flush()?;
+                Ok(())
+            }
+            #[cfg(feature = "compress-zstd")]
+            CompressedWriter::Zstd(enc) => {
+                enc.finish()?;
+                Ok(())
+            }
+            #[cfg(feature = "compress-gzip")]
+            CompressedWriter::Gzip(enc) => {
+                enc.finish()?;
+                Ok(())
+            }
+            #[cfg(feature = "compress-bzip2")]
+            CompressedWriter::Bzip2(enc) => {
+                enc.finish()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for CompressedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            CompressedWriter::None(w) => w.write(buf),
+            #[cfg(feature = "compress-zstd")]
+            CompressedWriter::Zstd(w) => w.write(buf),
+            #[cfg(feature = "compress-gzip")]
+            CompressedWriter::Gzip(w) => w.write(buf),
+            #[cfg(feature = "compress-bzip2")]
+            CompressedWriter::Bzip2(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            CompressedWriter::None(w) => w.flush(),
+            #[cfg(feature = "compress-zstd")]
+            CompressedWriter::Zstd(w) => w.flush(),
+            #[cfg(feature = "compress-gzip")]
+            CompressedWriter::Gzip(w) => w.flush(),
+            #[cfg(feature = "compress-bzip2")]
+            CompressedWriter::Bzip2(w) => w.flush(),
+        }
+    }
+}
+
+/// Open `path` for writing, transparently compressing based on its extension.
+fn open_writer(path: &Path) -> Result<CompressedWriter> {
+    let file = File::create(path)?;
+    let writer = BufWriter::new(file);
+
+    match detect_compression(path) {
+        Compression::None => Ok(CompressedWriter::None(writer)),
+        Compression::Zstd => {
+            #[cfg(feature = "compress-zstd")]
+            {
+                Ok(CompressedWriter::Zstd(zstd::Encoder::new(writer, 0)?))
+            }
+            #[cfg(not(feature = "compress-zstd"))]
+            {
+                Err(anyhow!(
+                    "{} requests zstd compression but the `compress-zstd` feature is not enabled",
+                    path.display()
+                ))
+            }
+        }
+        Compression::Gzip => {
+            #[cfg(feature = "compress-gzip")]
+            {
+                Ok(CompressedWriter::Gzip(flate2::write::GzEncoder::new(
+                    writer,
+                    flate2::Compression::default(),
+                )))
+            }
+            #[cfg(not(feature = "compress-gzip"))]
+            {
+                Err(anyhow!(
+                    "{} requests gzip compression but the `compress-gzip` feature is not enabled",
+                    path.display()
+                ))
+            }
+        }
+        Compression::Bzip2 => {
+            #[cfg(feature = "compress-bzip2")]
+            {
+                Ok(CompressedWriter::Bzip2(bzip2::write::BzEncoder::new(
+                    writer,
+                    bzip2::Compression::default(),
+                )))
+            }
+            #[cfg(not(feature = "compress-bzip2"))]
+            {
+                Err(anyhow!(
+                    "{} requests bzip2 compression but the `compress-bzip2` feature is not enabled",
+                    path.display()
+                ))
+            }
+        }
+    }
+}
+
 pub struct Hypergraph {
     pub num_nodes: u32,
     pub num_hyperedges: u32,
@@ -11,11 +199,38 @@ pub struct Hypergraph {
     pub hyperedge_nodes: Vec<i32>,
     pub node_offsets: Vec<i32>,
     pub node_hyperedges: Vec<i32>,
+    /// Per-hyperedge weight, defaulting to 1 when the .hgr file has no `fmt`
+    /// header token (or `fmt` doesn't request hyperedge weights).
+    pub hyperedge_weights: Vec<i32>,
+    /// Per-node weight, defaulting to 1 when the .hgr file has no trailing
+    /// node-weight block (`fmt` 10 or 11).
+    pub node_weights: Vec<i32>,
+}
+
+/// hMETIS `fmt` header token: bit 0 means each hyperedge line is prefixed by
+/// its weight, bit 1 means a trailing block of per-node weights follows the
+/// hyperedge lines (values 0, 1, 10, 11 — read as the two flags side by
+/// side, not a binary bitmask).
+struct HgrFormat {
+    hyperedge_weights: bool,
+    node_weights: bool,
+}
+
+impl HgrFormat {
+    fn parse(token: Option<&str>) -> Result<Self> {
+        match token {
+            None => Ok(Self { hyperedge_weights: false, node_weights: false }),
+            Some("0") => Ok(Self { hyperedge_weights: false, node_weights: false }),
+            Some("1") => Ok(Self { hyperedge_weights: true, node_weights: false }),
+            Some("10") => Ok(Self { hyperedge_weights: false, node_weights: true }),
+            Some("11") => Ok(Self { hyperedge_weights: true, node_weights: true }),
+            Some(other) => Err(anyhow!("Unsupported .hgr fmt token '{}'", other)),
+        }
+    }
 }
 
 pub fn read_hgr(path: &Path) -> Result<Hypergraph> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    let reader = open_reader(path)?;
     let mut lines = reader.lines();
 
     let header = lines
@@ -28,20 +243,32 @@ pub fn read_hgr(path: &Path) -> Result<Hypergraph> {
 
     let num_hyperedges: u32 = parts[0].parse()?;
     let num_nodes: u32 = parts[1].parse()?;
+    let fmt = HgrFormat::parse(parts.get(2).copied())?;
 
     let mut hyperedge_offsets: Vec<i32> = Vec::with_capacity(num_hyperedges as usize + 1);
     let mut hyperedge_nodes: Vec<i32> = Vec::new();
+    let mut hyperedge_weights: Vec<i32> = Vec::with_capacity(num_hyperedges as usize);
 
     hyperedge_offsets.push(0);
 
-    for line in lines {
-        let line = line?;
+    for _ in 0..num_hyperedges {
+        let line = lines
+            .next()
+            .ok_or_else(|| anyhow!("Expected {} hyperedge lines, ran out early", num_hyperedges))??;
         let line = line.trim();
-        if line.is_empty() {
-            continue;
+
+        let mut tokens = line.split_whitespace();
+        if fmt.hyperedge_weights {
+            let weight: i32 = tokens
+                .next()
+                .ok_or_else(|| anyhow!("Missing hyperedge weight in weighted .hgr file"))?
+                .parse()?;
+            hyperedge_weights.push(weight);
+        } else {
+            hyperedge_weights.push(1);
         }
 
-        for node_str in line.split_whitespace() {
+        for node_str in tokens {
             let node: i32 = node_str.parse()?;
             hyperedge_nodes.push(node - 1);
         }
@@ -56,6 +283,19 @@ pub fn read_hgr(path: &Path) -> Result<Hypergraph> {
         ));
     }
 
+    let node_weights = if fmt.node_weights {
+        let mut weights = Vec::with_capacity(num_nodes as usize);
+        for _ in 0..num_nodes {
+            let line = lines
+                .next()
+                .ok_or_else(|| anyhow!("Expected {} node weight lines, ran out early", num_nodes))??;
+            weights.push(line.trim().parse()?);
+        }
+        weights
+    } else {
+        vec![1; num_nodes as usize]
+    };
+
     let (node_offsets, node_hyperedges) = build_node_to_hyperedge(
         num_nodes as usize,
         &hyperedge_offsets,
@@ -69,10 +309,15 @@ pub fn read_hgr(path: &Path) -> Result<Hypergraph> {
         hyperedge_nodes,
         node_offsets,
         node_hyperedges,
+        hyperedge_weights,
+        node_weights,
     })
 }
 
-fn build_node_to_hyperedge(
+/// CPU counting-sort CSR transpose. `pub(crate)` so `cpu_solver` can rebuild
+/// node adjacency for contracted (coarsened) hypergraphs without duplicating
+/// this pass.
+pub(crate) fn build_node_to_hyperedge(
     num_nodes: usize,
     hyperedge_offsets: &[i32],
     hyperedge_nodes: &[i32],
@@ -115,18 +360,156 @@ fn build_node_to_hyperedge(
     (node_offsets, node_hyperedges)
 }
 
+/// GPU counting-sort implementation of [`build_node_to_hyperedge`], for
+/// callers that already hold a `GpuContext` and the hyperedge CSR uploaded
+/// as device slices (see `solver::hypergraph_to_challenge`, the only call
+/// site: it needs `d_hyperedge_offsets`/`d_hyperedge_nodes` uploaded anyway
+/// for the `Challenge` it builds, so this reuses that upload instead of
+/// paying for a second one).
+///
+/// `node_offsets` comes back host-side -- callers need it to compute
+/// per-node degree, and at `num_nodes + 1` entries it's cheap to download.
+/// `node_hyperedges` is the array the CPU path's `build_node_to_hyperedge`
+/// would materialize on host and the caller would then re-upload; here it
+/// stays device-resident and is returned as a `CudaSlice` instead, so it
+/// never round-trips through the host. Only the `num_nodes`-sized degree
+/// and offset arrays cross over, for the host-side prefix sum.
+#[cfg(feature = "gpu-csr-transpose")]
+pub fn build_node_to_hyperedge_gpu(
+    ctx: &crate::cuda::GpuContext,
+    num_nodes: usize,
+    num_hyperedges: usize,
+    total_pins: usize,
+    d_hyperedge_offsets: &cudarc::driver::CudaSlice<i32>,
+    d_hyperedge_nodes: &cudarc::driver::CudaSlice<i32>,
+) -> Result<(Vec<i32>, cudarc::driver::CudaSlice<i32>, cudarc::driver::CudaSlice<i32>)> {
+    use cudarc::driver::{CudaSlice, PushKernelArg};
+    use cudarc::nvrtc::Ptx;
+
+    let ptx_bytes = include_bytes!(concat!(env!("OUT_DIR"), "/csr_transpose.ptx"));
+    let ptx = Ptx::from_src(std::str::from_utf8(ptx_bytes)?);
+    let module = ctx.ctx.load_module(ptx).map_err(|e| anyhow!("Failed to load csr_transpose PTX: {:?}", e))?;
+    let histogram_fn = module
+        .load_function("csr_transpose_histogram")
+        .map_err(|e| anyhow!("Failed to load csr_transpose_histogram: {:?}", e))?;
+    let scatter_fn = module
+        .load_function("csr_transpose_scatter")
+        .map_err(|e| anyhow!("Failed to load csr_transpose_scatter: {:?}", e))?;
+
+    let mut d_node_degrees: CudaSlice<i32> = ctx.stream.alloc_zeros(num_nodes)?;
+
+    let pin_cfg = crate::cuda::launch_config_1d(total_pins as u32);
+    ctx.stream
+        .launch_builder(&histogram_fn)
+        .arg(d_hyperedge_nodes)
+        .arg(&(total_pins as i32))
+        .arg(&(num_nodes as i32))
+        .arg(&mut d_node_degrees)
+        .launch(pin_cfg)
+        .map_err(|e| anyhow!("csr_transpose_histogram launch failed: {:?}", e))?;
+
+    let node_degrees: Vec<i32> = ctx.stream.memcpy_dtov(&d_node_degrees)?;
+    let mut node_offsets = vec![0i32; num_nodes + 1];
+    for i in 0..num_nodes {
+        node_offsets[i + 1] = node_offsets[i] + node_degrees[i];
+    }
+    let total_connections = node_offsets[num_nodes] as usize;
+
+    let d_node_offsets: CudaSlice<i32> = ctx.stream.memcpy_stod(&node_offsets)?;
+    let mut d_node_current: CudaSlice<i32> = ctx.stream.alloc_zeros(num_nodes)?;
+    let mut d_node_hyperedges: CudaSlice<i32> = ctx.stream.alloc_zeros(total_connections)?;
+
+    let hedge_cfg = crate::cuda::launch_config_1d(num_hyperedges as u32);
+    ctx.stream
+        .launch_builder(&scatter_fn)
+        .arg(d_hyperedge_offsets)
+        .arg(d_hyperedge_nodes)
+        .arg(&(num_hyperedges as i32))
+        .arg(&(num_nodes as i32))
+        .arg(&d_node_offsets)
+        .arg(&mut d_node_current)
+        .arg(&mut d_node_hyperedges)
+        .launch(hedge_cfg)
+        .map_err(|e| anyhow!("csr_transpose_scatter launch failed: {:?}", e))?;
+
+    Ok((node_offsets, d_node_offsets, d_node_hyperedges))
+}
+
+/// Whether `hg` carries non-trivial hyperedge or node weights -- i.e. more
+/// than the uniform 1s [`Hypergraph`]'s fields default to when a .hgr file
+/// has no weight block. Used to reject the GPU backend for weighted
+/// instances (see `solver::hypergraph_to_challenge`), since the vendored
+/// `Challenge` type it uploads to has no weight fields to carry them.
+pub fn is_weighted(hg: &Hypergraph) -> bool {
+    hg.hyperedge_weights.iter().any(|&w| w != 1) || hg.node_weights.iter().any(|&w| w != 1)
+}
+
 #[allow(dead_code)]
 pub fn write_hgr(path: &Path, hypergraph: &Hypergraph) -> Result<()> {
-    let file = File::create(path)?;
-    let mut writer = BufWriter::new(file);
+    let mut writer = open_writer(path)?;
+
+    let has_hyperedge_weights = hypergraph.hyperedge_weights.iter().any(|&w| w != 1);
+    let has_node_weights = hypergraph.node_weights.iter().any(|&w| w != 1);
 
-    writeln!(writer, "{} {}", hypergraph.num_hyperedges, hypergraph.num_nodes)?;
+    let fmt = match (has_hyperedge_weights, has_node_weights) {
+        (false, false) => None,
+        (true, false) => Some("1"),
+        (false, true) => Some("10"),
+        (true, true) => Some("11"),
+    };
+
+    match fmt {
+        Some(fmt) => writeln!(writer, "{} {} {}", hypergraph.num_hyperedges, hypergraph.num_nodes, fmt)?,
+        None => writeln!(writer, "{} {}", hypergraph.num_hyperedges, hypergraph.num_nodes)?,
+    }
 
     for i in 0..hypergraph.num_hyperedges as usize {
         let start = hypergraph.hyperedge_offsets[i] as usize;
         let end = hypergraph.hyperedge_offsets[i + 1] as usize;
 
-        let nodes: Vec<String> = hypergraph.hyperedge_nodes[start..end]
+        let mut tokens: Vec<String> = Vec::new();
+        if has_hyperedge_weights {
+            tokens.push(hypergraph.hyperedge_weights[i].to_string());
+        }
+        tokens.extend(
+            hypergraph.hyperedge_nodes[start..end]
+                .iter()
+                .map(|&n| (n + 1).to_string()),
+        );
+
+        writeln!(writer, "{}", tokens.join(" "))?;
+    }
+
+    if has_node_weights {
+        for &weight in &hypergraph.node_weights {
+            writeln!(writer, "{}", weight)?;
+        }
+    }
+
+    writer.finish()
+}
+
+/// Writes a hyperedge CSR directly to `path` in hMETIS text form, transparently
+/// compressing by extension like [`write_hgr`]. For callers that already have
+/// `hyperedge_offsets`/`hyperedge_nodes` (e.g. `gen_hgr`, which reads them
+/// straight off a GPU-resident `Challenge` rather than building a
+/// [`Hypergraph`]) and don't need the weight-block handling `write_hgr` does.
+pub fn write_hgr_edges(
+    path: &Path,
+    num_hyperedges: u32,
+    num_nodes: u32,
+    hyperedge_offsets: &[i32],
+    hyperedge_nodes: &[i32],
+) -> Result<()> {
+    let mut writer = open_writer(path)?;
+
+    writeln!(writer, "{} {}", num_hyperedges, num_nodes)?;
+
+    for i in 0..num_hyperedges as usize {
+        let start = hyperedge_offsets[i] as usize;
+        let end = hyperedge_offsets[i + 1] as usize;
+
+        let nodes: Vec<String> = hyperedge_nodes[start..end]
             .iter()
             .map(|&n| (n + 1).to_string())
             .collect();
@@ -134,13 +517,11 @@ pub fn write_hgr(path: &Path, hypergraph: &Hypergraph) -> Result<()> {
         writeln!(writer, "{}", nodes.join(" "))?;
     }
 
-    writer.flush()?;
-    Ok(())
+    writer.finish()
 }
 
 pub fn read_partition(path: &Path) -> Result<Vec<u32>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
+    let reader = open_reader(path)?;
     let mut partition = Vec::new();
 
     for line in reader.lines() {
@@ -155,15 +536,13 @@ pub fn read_partition(path: &Path) -> Result<Vec<u32>> {
 }
 
 pub fn write_partition(path: &Path, partition: &[u32]) -> Result<()> {
-    let file = File::create(path)?;
-    let mut writer = BufWriter::new(file);
+    let mut writer = open_writer(path)?;
 
     for &part in partition {
         writeln!(writer, "{}", part)?;
     }
 
-    writer.flush()?;
-    Ok(())
+    writer.finish()
 }
 
 #[allow(dead_code)]
@@ -177,6 +556,10 @@ pub fn write_partition_with_timing(path: &Path, partition: &[u32], elapsed_secs:
     Ok(())
 }
 
+/// Weighted (lambda-1) connectivity: each cut hyperedge contributes
+/// `(parts_spanned - 1) * hyperedge_weight` instead of counting every cut
+/// edge equally. Unweighted instances default every `hyperedge_weights`
+/// entry to 1, so this matches the old unweighted metric exactly.
 pub fn compute_connectivity(hypergraph: &Hypergraph, partition: &[u32]) -> u32 {
     let mut connectivity = 0u32;
 
@@ -192,19 +575,35 @@ pub fn compute_connectivity(hypergraph: &Hypergraph, partition: &[u32]) -> u32 {
         }
 
         if parts_in_edge.len() > 1 {
-            connectivity += (parts_in_edge.len() - 1) as u32;
+            let weight = hypergraph.hyperedge_weights.get(i).copied().unwrap_or(1).max(0) as u32;
+            connectivity += (parts_in_edge.len() - 1) as u32 * weight;
         }
     }
 
     connectivity
 }
 
-pub fn check_feasibility(partition: &[u32], k: u32, max_part_size: u32) -> (bool, u32, u32) {
+/// Per-part weight budget: total node weight spread evenly across `k` parts,
+/// inflated by `epsilon`. Takes `node_weights` (not vertex count) so the
+/// budget is in the same units [`check_feasibility`] sums against -- on an
+/// unweighted instance (every weight 1) this is the same value the old
+/// vertex-count formula gave.
+pub fn max_part_size(node_weights: &[i32], k: u32, epsilon: f64) -> u32 {
+    let total_weight: i64 = node_weights.iter().map(|&w| w.max(0) as i64).sum();
+    ((total_weight as f64 / k as f64) * (1.0 + epsilon)).ceil() as u32
+}
+
+/// Checks balance by summed node weight per part rather than vertex count,
+/// so `max_part_size` is honored in the same units the partitioner balanced
+/// against. Unweighted instances have every `node_weights` entry equal to 1,
+/// so this matches the old vertex-counting behavior exactly.
+pub fn check_feasibility(partition: &[u32], node_weights: &[i32], k: u32, max_part_size: u32) -> (bool, u32, u32) {
     let mut part_sizes = vec![0u32; k as usize];
 
-    for &p in partition {
+    for (i, &p) in partition.iter().enumerate() {
         if (p as usize) < part_sizes.len() {
-            part_sizes[p as usize] += 1;
+            let weight = node_weights.get(i).copied().unwrap_or(1).max(0) as u32;
+            part_sizes[p as usize] += weight;
         }
     }
 
@@ -215,3 +614,112 @@ pub fn check_feasibility(partition: &[u32], k: u32, max_part_size: u32) -> (bool
 
     (is_feasible, max_size, min_size)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_hypergraph() -> Hypergraph {
+        // 3 hyperedges over 4 nodes: {0,1}, {1,2,3}, {0,3}.
+        let hyperedge_offsets = vec![0, 2, 5, 7];
+        let hyperedge_nodes = vec![0, 1, 1, 2, 3, 0, 3];
+        let (node_offsets, node_hyperedges) =
+            build_node_to_hyperedge(4, &hyperedge_offsets, &hyperedge_nodes);
+        Hypergraph {
+            num_nodes: 4,
+            num_hyperedges: 3,
+            hyperedge_offsets,
+            hyperedge_nodes,
+            node_offsets,
+            node_hyperedges,
+            hyperedge_weights: vec![1, 1, 1],
+            node_weights: vec![1, 1, 1, 1],
+        }
+    }
+
+    #[test]
+    fn write_read_hgr_round_trip() {
+        let mut hg = sample_hypergraph();
+        hg.hyperedge_weights = vec![3, 1, 2];
+        hg.node_weights = vec![5, 1, 1, 2];
+
+        let path = std::env::temp_dir().join(format!(
+            "hg_bench_test_round_trip_{}_{}.hgr",
+            std::process::id(),
+            "write_read_hgr_round_trip"
+        ));
+        write_hgr(&path, &hg).unwrap();
+        let read_back = read_hgr(&path).unwrap();
+        fs_remove(&path);
+
+        assert_eq!(read_back.num_nodes, hg.num_nodes);
+        assert_eq!(read_back.num_hyperedges, hg.num_hyperedges);
+        assert_eq!(read_back.hyperedge_offsets, hg.hyperedge_offsets);
+        assert_eq!(read_back.hyperedge_nodes, hg.hyperedge_nodes);
+        assert_eq!(read_back.hyperedge_weights, hg.hyperedge_weights);
+        assert_eq!(read_back.node_weights, hg.node_weights);
+    }
+
+    fn fs_remove(path: &Path) {
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn compute_connectivity_is_unweighted_by_default() {
+        let hg = sample_hypergraph();
+        // {0,1} same part, {1,2,3} split across 2 parts, {0,3} same part.
+        let partition = vec![0, 0, 1, 0];
+        assert_eq!(compute_connectivity(&hg, &partition), 1);
+    }
+
+    #[test]
+    fn compute_connectivity_scales_by_hyperedge_weight() {
+        let mut hg = sample_hypergraph();
+        hg.hyperedge_weights = vec![1, 5, 1];
+        let partition = vec![0, 0, 1, 0];
+        // Only the weight-5 hyperedge is cut, spanning 2 parts: (2-1) * 5.
+        assert_eq!(compute_connectivity(&hg, &partition), 5);
+    }
+
+    #[test]
+    fn max_part_size_matches_vertex_count_formula_when_unweighted() {
+        let node_weights = vec![1; 100];
+        assert_eq!(max_part_size(&node_weights, 10, 0.03), 11);
+    }
+
+    #[test]
+    fn max_part_size_sums_weight_not_vertex_count() {
+        // 1000 nodes at weight ~1000 each should NOT collapse to ~103 the way
+        // a vertex-count-based budget would.
+        let node_weights = vec![1000; 1000];
+        let budget = max_part_size(&node_weights, 10, 0.03);
+        assert_eq!(budget, 103_000);
+    }
+
+    #[test]
+    fn check_feasibility_sums_weight_per_part() {
+        let partition = vec![0, 0, 1, 1];
+        let node_weights = vec![3, 2, 4, 1];
+        // part 0: 3+2=5, part 1: 4+1=5
+        let (feasible, max_size, min_size) = check_feasibility(&partition, &node_weights, 2, 5);
+        assert!(feasible);
+        assert_eq!(max_size, 5);
+        assert_eq!(min_size, 5);
+
+        let (feasible, max_size, _) = check_feasibility(&partition, &node_weights, 2, 4);
+        assert!(!feasible);
+        assert_eq!(max_size, 5);
+    }
+
+    #[test]
+    fn check_feasibility_defaults_missing_weights_to_one() {
+        // node_weights shorter than partition: entries past the end default
+        // to weight 1, matching an unweighted instance.
+        let partition = vec![0, 1, 0];
+        let node_weights = vec![1];
+        let (feasible, max_size, min_size) = check_feasibility(&partition, &node_weights, 2, 2);
+        assert!(feasible);
+        assert_eq!(max_size, 2);
+        assert_eq!(min_size, 1);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod cpu_solver;
+pub mod cuda;
+pub mod hgr;
+pub mod profile;
+pub mod scheduler;
+pub mod solver;
@@ -1,12 +1,22 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::{Parser, Subcommand};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::time::Instant;
 
-mod cuda;
-mod hgr;
-mod solver;
+use hg_bench::{cpu_solver, cuda, hgr, profile, scheduler, solver};
+use profile::Profiler;
+
+/// Parse the `--gpus` flag shared by commands that fan work out across a
+/// `scheduler::GpuPool`: "all" or a comma-separated list of device ordinals.
+fn parse_gpus(spec: &str) -> Result<Option<Vec<usize>>> {
+    if spec.eq_ignore_ascii_case("all") {
+        return Ok(None);
+    }
+    let devices: Result<Vec<usize>, _> = spec.split(',').map(|s| s.trim().parse::<usize>()).collect();
+    Ok(Some(devices.map_err(|e| anyhow!("Invalid --gpus list '{}': {}", spec, e))?))
+}
 
 #[derive(Parser)]
 #[command(name = "hg_bench")]
@@ -40,6 +50,41 @@ enum Commands {
         /// Refinement rounds (overrides effort-based default if specified)
         #[arg(short, long)]
         refinement: Option<u32>,
+
+        /// CUDA devices to spread nonces across: "all" or a comma-separated
+        /// list of ordinals (e.g. "0,1,3"). Defaults to every visible device.
+        #[arg(long, default_value = "all")]
+        gpus: String,
+
+        /// Backend used to solve each generated instance: "gpu" (the track
+        /// kernels), "cpu" (the reference multilevel partitioner), or "auto"
+        /// (gpu, falling back to cpu if no CUDA device is visible). Instance
+        /// generation itself always runs on the GPU.
+        #[arg(long, default_value = "auto")]
+        device: String,
+
+        /// Solve this many leading instances (in nonce order) but exclude
+        /// them from the aggregated statistics, so JIT warmup, allocator
+        /// warmup, and clock ramp don't skew the reported percentiles.
+        #[arg(long, default_value = "0")]
+        warmup: u32,
+
+        /// Write one row per instance plus an aggregate stats block to this
+        /// path. Format is inferred from the extension: ".csv" or ".json".
+        #[arg(long)]
+        report: Option<PathBuf>,
+
+        /// Collect per-phase cost centers and write them as JSON to this
+        /// path, plus a summary table on stdout. On the CPU backend this
+        /// breaks down into coarsen/initial-partition/refine phases; on the
+        /// GPU backend the track kernels (`solver::track_10k` etc.) are an
+        /// opaque external entry point this crate doesn't see inside of, so
+        /// `--device gpu` only gets instance generation, device download,
+        /// and a single `gpu_track_solve` span for the whole dispatch.
+        /// Adds `Instant::now()`/mutex overhead around each phase, so it's
+        /// opt-in rather than always-on.
+        #[arg(long)]
+        profile: Option<PathBuf>,
     },
 
     /// Solve an existing .hgr file
@@ -67,6 +112,29 @@ enum Commands {
         /// Refinement rounds (overrides effort-based default if specified)
         #[arg(long)]
         refinement: Option<u32>,
+
+        /// Backend used to solve: "gpu", "cpu", or "auto" (gpu, falling back
+        /// to cpu if no CUDA device is visible).
+        #[arg(long, default_value = "auto")]
+        device: String,
+    },
+
+    /// Run as a persistent solver daemon: build the GPU context once per
+    /// session (the whole stdin stream, or one TCP connection; each
+    /// `--listen` connection builds its own) and service requests over a
+    /// line-delimited JSON protocol, so back-to-back solves within a session
+    /// skip PTX load and device-prop queries entirely.
+    Serve {
+        /// TCP address to listen on (e.g. "127.0.0.1:9090"). When omitted,
+        /// requests are read line-delimited from stdin and responses are
+        /// written line-delimited to stdout.
+        #[arg(long)]
+        listen: Option<String>,
+
+        /// Backend used for requests that don't set their own "device":
+        /// "gpu", "cpu", or "auto".
+        #[arg(long, default_value = "auto")]
+        device: String,
     },
 
     /// Verify a partition and compute metrics
@@ -99,93 +167,156 @@ fn main() -> Result<()> {
             out,
             effort,
             refinement,
+            gpus,
+            device,
+            warmup,
+            report,
+            profile,
         } => {
             use tig_challenges::hypergraph::{Challenge, Solution, Track};
             use serde_json::{Map, Value};
-            
+            use std::sync::Arc;
+
+            let device: solver::Device = device.parse()?;
+            let profiler = Arc::new(Profiler::new(profile.is_some()));
+
             println!("Generating {} instances for track {} hyperedges", nonces, track);
             println!("Output directory: {}", out.display());
             println!("Effort: {}, Refinement: {:?}", effort, refinement);
-            
+            println!("Solve backend: {:?}", device);
+
             fs::create_dir_all(&out)?;
-            
-            let ctx = cuda::GpuContext::new()?;
-            let tig_track = Track { n_h_edges: track };
-            
+
+            let devices = parse_gpus(&gpus)?;
+            let pool = scheduler::GpuPool::new(devices)?;
+            println!("Devices: {}", pool.num_devices());
+
             let mut hyperparameters: Map<String, Value> = Map::new();
             hyperparameters.insert("effort".to_string(), Value::Number(effort.into()));
             if let Some(r) = refinement {
                 hyperparameters.insert("refinement".to_string(), Value::Number(r.into()));
             }
             let hyperparameters = Some(hyperparameters);
-            
-            let mut total_connectivity = 0u64;
-            let mut total_time = 0.0f64;
-            
-            for nonce in 0..nonces {
-                let seed = generate_tig_seed(track, nonce as u64);
+
+            let nonces_list: Vec<u64> = (0..nonces as u64).collect();
+            let out_for_workers = out.clone();
+            let solve_on_gpu = solver::use_gpu(device);
+            let profiler_for_workers = profiler.clone();
+
+            let results = pool.run(nonces_list, move |ctx, nonce| -> Result<InstanceResult> {
+                let profiler = profiler_for_workers.as_ref();
+                let seed = generate_tig_seed(track, nonce);
                 let seed_hex = format!("{:02x}{:02x}{:02x}{:02x}", seed[0], seed[1], seed[2], seed[3]);
-                
-                println!("\n[{}/{}] Generating instance with seed {}...", nonce + 1, nonces, seed_hex);
-                
-                let challenge = Challenge::generate_instance(
-                    &seed,
-                    &tig_track,
-                    ctx.module.clone(),
-                    ctx.stream.clone(),
-                    &ctx.prop,
-                )?;
-                
-                println!("  Nodes: {}, Hyperedges: {}, k: {}, max_part_size: {}", 
-                    challenge.num_nodes, challenge.num_hyperedges, challenge.num_parts, challenge.max_part_size);
-                
-                let hgr_path = out.join(format!("challenge_{}_{}.hgr", track, seed_hex));
-                export_challenge_to_hgr(&challenge, &hgr_path, &ctx)?;
-                println!("  Exported .hgr to: {}", hgr_path.display());
-                
-                let final_partition: std::cell::RefCell<Vec<u32>> = std::cell::RefCell::new(Vec::new());
-                let save_solution = |solution: &Solution| -> anyhow::Result<()> {
-                    *final_partition.borrow_mut() = solution.partition.clone();
-                    Ok(())
-                };
-                
+
+                let tig_track = Track { n_h_edges: track };
+                let challenge = profiler.record("generate_instance", || {
+                    Challenge::generate_instance(&seed, &tig_track, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)
+                })?;
+
+                let hgr_path = out_for_workers.join(format!("challenge_{}_{}.hgr", track, seed_hex));
+                export_challenge_to_hgr(&challenge, &hgr_path, ctx, profiler)?;
+                let hg = hgr::read_hgr(&hgr_path)?;
+
                 let start = Instant::now();
-                
-                match track {
-                    0..=15000 => solver::track_10k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
-                    15001..=30000 => solver::track_20k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
-                    30001..=75000 => solver::track_50k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
-                    75001..=150000 => solver::track_100k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
-                    _ => solver::track_200k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
-                }
-                
+
+                let partition: Vec<u32> = if solve_on_gpu {
+                    let final_partition: std::cell::RefCell<Vec<u32>> = std::cell::RefCell::new(Vec::new());
+                    let save_solution = |solution: &Solution| -> anyhow::Result<()> {
+                        *final_partition.borrow_mut() = solution.partition.clone();
+                        Ok(())
+                    };
+
+                    // `track_N::solve` is the external vendored entry point for
+                    // this track's kernels: it only takes module/stream/prop
+                    // and computes its own launch configs and any internal
+                    // coarsening/refinement passes without exposing them back
+                    // to this crate. So unlike the CPU backend's
+                    // cpu_coarsen/cpu_initial_partition/cpu_refine split,
+                    // this is necessarily one opaque span covering the whole
+                    // dispatch, not a per-phase breakdown.
+                    profiler.record("gpu_track_solve", || -> Result<()> {
+                        match track {
+                            0..=15000 => solver::track_10k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
+                            15001..=30000 => solver::track_20k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
+                            30001..=75000 => solver::track_50k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
+                            75001..=150000 => solver::track_100k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
+                            _ => solver::track_200k::solve(&challenge, &save_solution, &hyperparameters, ctx.module.clone(), ctx.stream.clone(), &ctx.prop)?,
+                        }
+                        Ok(())
+                    })?;
+
+                    final_partition.into_inner()
+                } else {
+                    cpu_solver::solve_with_profiler(&hg, challenge.num_parts, challenge.max_part_size, effort, refinement, Some(profiler))?
+                };
+
                 let elapsed = start.elapsed().as_secs_f64();
-                total_time += elapsed;
-                
-                let partition = final_partition.borrow();
-                let partition_path = out.join(format!("partition_{}_{}.txt", track, seed_hex));
+
+                let partition_path = out_for_workers.join(format!("partition_{}_{}.txt", track, seed_hex));
                 hgr::write_partition(&partition_path, &partition)?;
-                
-                // Write timing file for comparison script
-                let timing_path = out.join(format!("partition_{}_{}_timing.txt", track, seed_hex));
+
+                let timing_path = out_for_workers.join(format!("partition_{}_{}_timing.txt", track, seed_hex));
                 fs::write(&timing_path, format!("{:.3}\n", elapsed))?;
-                
-                let hg = hgr::read_hgr(&hgr_path)?;
+
                 let connectivity = hgr::compute_connectivity(&hg, &partition);
-                total_connectivity += connectivity as u64;
-                
-                println!("  Connectivity (KM1): {}", connectivity);
-                println!("  Time: {:.2}s", elapsed);
-                println!("  Partition saved to: {}", partition_path.display());
+
+                Ok(InstanceResult {
+                    seed_hex,
+                    num_nodes: hg.num_nodes,
+                    num_hyperedges: hg.num_hyperedges,
+                    k: challenge.num_parts,
+                    connectivity,
+                    elapsed,
+                    device: ctx.device,
+                })
+            })?;
+
+            for (i, r) in results.iter().enumerate() {
+                println!(
+                    "[{}/{}] seed {} (device {}): Connectivity (KM1): {}, Time: {:.2}s",
+                    i + 1,
+                    results.len(),
+                    r.seed_hex,
+                    r.device,
+                    r.connectivity,
+                    r.elapsed
+                );
             }
-            
+
+            let warmup = (warmup as usize).min(results.len());
+            let measured = &results[warmup..];
+
+            let connectivity_stats = Stats::compute(&measured.iter().map(|r| r.connectivity as f64).collect::<Vec<_>>());
+            let time_stats = Stats::compute(&measured.iter().map(|r| r.elapsed).collect::<Vec<_>>());
+
             println!("\n=== Summary ===");
-            println!("Instances: {}", nonces);
+            println!("Instances: {} ({} warmup, {} measured)", nonces, warmup, measured.len());
             println!("Track: {} hyperedges", track);
-            println!("Total connectivity: {}", total_connectivity);
-            println!("Average connectivity: {:.1}", total_connectivity as f64 / nonces as f64);
-            println!("Total time: {:.2}s", total_time);
-            println!("Average time: {:.2}s", total_time / nonces as f64);
+            println!(
+                "Connectivity (KM1): min={:.1} median={:.1} p90={:.1} p99={:.1} max={:.1} mean={:.1} stddev={:.1}",
+                connectivity_stats.min,
+                connectivity_stats.median,
+                connectivity_stats.p90,
+                connectivity_stats.p99,
+                connectivity_stats.max,
+                connectivity_stats.mean,
+                connectivity_stats.stddev
+            );
+            println!(
+                "Time (s): min={:.3} median={:.3} p90={:.3} p99={:.3} max={:.3} mean={:.3} stddev={:.3}",
+                time_stats.min, time_stats.median, time_stats.p90, time_stats.p99, time_stats.max, time_stats.mean, time_stats.stddev
+            );
+
+            if let Some(report_path) = report {
+                write_report(&report_path, &results, warmup, &connectivity_stats, &time_stats)?;
+                println!("Report written to: {}", report_path.display());
+            }
+
+            if let Some(profile_path) = profile {
+                profiler.print_summary();
+                profiler.write_json(&profile_path)?;
+                println!("Profile written to: {}", profile_path.display());
+            }
         }
 
         Commands::File {
@@ -195,10 +326,14 @@ fn main() -> Result<()> {
             epsilon,
             effort,
             refinement,
+            device,
         } => {
+            let device: solver::Device = device.parse()?;
+
             println!("Solving: {}", hgr.display());
             println!("Output: {}", out.display());
             println!("k={}, epsilon={}, effort={}, refinement={:?}", k, epsilon, effort, refinement);
+            println!("Solve backend: {:?}", device);
 
             let hypergraph = hgr::read_hgr(&hgr)?;
             println!(
@@ -206,17 +341,11 @@ fn main() -> Result<()> {
                 hypergraph.num_nodes, hypergraph.num_hyperedges
             );
 
-            let max_part_size = ((hypergraph.num_nodes as f64 / k as f64) * (1.0 + epsilon)).ceil() as u32;
+            let max_part_size = hgr::max_part_size(&hypergraph.node_weights, k, epsilon);
             println!("Max partition size: {}", max_part_size);
 
             let start = Instant::now();
-            let partition = solver::solve(
-                &hypergraph,
-                k,
-                max_part_size,
-                effort,
-                refinement,
-            )?;
+            let partition = solver::solve(&hypergraph, k, max_part_size, effort, refinement, device)?;
             let elapsed = start.elapsed().as_secs_f64();
 
             hgr::write_partition(&out, &partition)?;
@@ -227,6 +356,15 @@ fn main() -> Result<()> {
             println!("Time: {:.2}s", elapsed);
         }
 
+        Commands::Serve { listen, device } => {
+            let default_device: solver::Device = device.parse()?;
+
+            match listen {
+                Some(addr) => serve_tcp(&addr, default_device)?,
+                None => serve_stdio(default_device)?,
+            }
+        }
+
         Commands::Score {
             hgr,
             partition,
@@ -240,10 +378,11 @@ fn main() -> Result<()> {
             let hypergraph = hgr::read_hgr(&hgr)?;
             let partition_vec = hgr::read_partition(&partition)?;
 
-            let max_part_size = ((hypergraph.num_nodes as f64 / k as f64) * (1.0 + epsilon)).ceil() as u32;
+            let max_part_size = hgr::max_part_size(&hypergraph.node_weights, k, epsilon);
 
             let connectivity = hgr::compute_connectivity(&hypergraph, &partition_vec);
-            let (is_feasible, max_size, min_size) = hgr::check_feasibility(&partition_vec, k, max_part_size);
+            let (is_feasible, max_size, min_size) =
+                hgr::check_feasibility(&partition_vec, &hypergraph.node_weights, k, max_part_size);
 
             println!("\n=== Results ===");
             println!("Nodes: {}", hypergraph.num_nodes);
@@ -261,6 +400,134 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Per-instance outcome collected by `Gen`'s worker closure, reused both for
+/// the stdout summary and the `--report` export.
+struct InstanceResult {
+    seed_hex: String,
+    num_nodes: u32,
+    num_hyperedges: u32,
+    k: u32,
+    connectivity: u32,
+    elapsed: f64,
+    device: usize,
+}
+
+/// Aggregate statistics over a set of samples (connectivity or wall time),
+/// reported instead of a bare average so a handful of slow/bad nonces don't
+/// hide in a mean.
+#[derive(Serialize, Default)]
+struct Stats {
+    min: f64,
+    median: f64,
+    p90: f64,
+    p99: f64,
+    max: f64,
+    mean: f64,
+    stddev: f64,
+}
+
+impl Stats {
+    fn compute(values: &[f64]) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = sorted.len();
+        let percentile = |p: f64| -> f64 {
+            let idx = ((p / 100.0) * (n - 1) as f64).round() as usize;
+            sorted[idx.min(n - 1)]
+        };
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+        Self {
+            min: sorted[0],
+            median: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+            max: sorted[n - 1],
+            mean,
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+/// Report row + aggregate block emitted by `Gen`'s `--report` flag, in a
+/// format chosen by `write_report` based on the path's extension.
+#[derive(Serialize)]
+struct Report<'a> {
+    warmup: usize,
+    instances: &'a [InstanceResult],
+    connectivity: &'a Stats,
+    time: &'a Stats,
+}
+
+impl serde::Serialize for InstanceResult {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("InstanceResult", 7)?;
+        s.serialize_field("seed", &self.seed_hex)?;
+        s.serialize_field("num_nodes", &self.num_nodes)?;
+        s.serialize_field("num_hyperedges", &self.num_hyperedges)?;
+        s.serialize_field("k", &self.k)?;
+        s.serialize_field("connectivity", &self.connectivity)?;
+        s.serialize_field("time", &self.elapsed)?;
+        s.serialize_field("device", &self.device)?;
+        s.end()
+    }
+}
+
+/// Writes `--report`'s per-instance rows plus an aggregate stats block.
+/// Format is inferred from `path`'s extension: `.json` for a single JSON
+/// document, `.csv` for a row-per-instance table with the aggregate block
+/// appended after a blank line (this repo has no `csv` dependency, so the
+/// rows are written by hand; seed hex and device ordinals can't contain a
+/// comma).
+fn write_report(
+    path: &std::path::Path,
+    results: &[InstanceResult],
+    warmup: usize,
+    connectivity: &Stats,
+    time: &Stats,
+) -> Result<()> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => {
+            let report = Report { warmup, instances: results, connectivity, time };
+            fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        }
+        Some("csv") => {
+            let mut out = String::from("seed,num_nodes,num_hyperedges,k,connectivity,time,device\n");
+            for r in results {
+                out.push_str(&format!(
+                    "{},{},{},{},{},{:.6},{}\n",
+                    r.seed_hex, r.num_nodes, r.num_hyperedges, r.k, r.connectivity, r.elapsed, r.device
+                ));
+            }
+            out.push_str(&format!("\nwarmup,{}\n", warmup));
+            out.push_str("metric,min,median,p90,p99,max,mean,stddev\n");
+            out.push_str(&format!(
+                "connectivity,{:.1},{:.1},{:.1},{:.1},{:.1},{:.1},{:.1}\n",
+                connectivity.min, connectivity.median, connectivity.p90, connectivity.p99, connectivity.max, connectivity.mean, connectivity.stddev
+            ));
+            out.push_str(&format!(
+                "time,{:.6},{:.6},{:.6},{:.6},{:.6},{:.6},{:.6}\n",
+                time.min, time.median, time.p90, time.p99, time.max, time.mean, time.stddev
+            ));
+            fs::write(path, out)?;
+        }
+        other => {
+            return Err(anyhow!(
+                "Unsupported --report extension {:?}; expected a path ending in .csv or .json",
+                other
+            ))
+        }
+    }
+    Ok(())
+}
+
 /// Generate seed using TIG's exact method:
 /// seed = blake3(jsonify(BenchmarkSettings) + "_" + rand_hash + "_" + nonce)
 /// 
@@ -299,13 +566,20 @@ fn export_challenge_to_hgr(
     challenge: &tig_challenges::hypergraph::Challenge,
     path: &PathBuf,
     ctx: &cuda::GpuContext,
+    profiler: &Profiler,
 ) -> Result<()> {
     use std::io::{BufWriter, Write};
     use std::fs::File;
-    
-    let hyperedge_offsets: Vec<i32> = ctx.stream.memcpy_dtov(&challenge.d_hyperedge_offsets)?;
-    let hyperedge_nodes: Vec<i32> = ctx.stream.memcpy_dtov(&challenge.d_hyperedge_nodes)?;
-    
+
+    let offsets_bytes = (challenge.num_hyperedges as u64 + 1) * 4;
+    let nodes_bytes = challenge.total_connections as u64 * 4;
+    let (hyperedge_offsets, hyperedge_nodes): (Vec<i32>, Vec<i32>) =
+        profiler.record_bytes("d2h_challenge_download", offsets_bytes + nodes_bytes, || -> Result<_> {
+            let hyperedge_offsets: Vec<i32> = ctx.stream.memcpy_dtov(&challenge.d_hyperedge_offsets)?;
+            let hyperedge_nodes: Vec<i32> = ctx.stream.memcpy_dtov(&challenge.d_hyperedge_nodes)?;
+            Ok((hyperedge_offsets, hyperedge_nodes))
+        })?;
+
     let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
     
@@ -326,3 +600,233 @@ fn export_challenge_to_hgr(
     writer.flush()?;
     Ok(())
 }
+
+/// One line of the `Serve` protocol's request side. Exactly one of
+/// `hgr_path`/`hgr_inline` must be set. `device` overrides the server's
+/// `--device` default for this request only.
+#[derive(Deserialize)]
+struct ServeRequest {
+    hgr_path: Option<PathBuf>,
+    hgr_inline: Option<String>,
+    out: Option<PathBuf>,
+    k: u32,
+    #[serde(default = "default_epsilon")]
+    epsilon: f64,
+    #[serde(default = "default_effort")]
+    effort: u32,
+    refinement: Option<u32>,
+    device: Option<String>,
+}
+
+fn default_epsilon() -> f64 {
+    0.03
+}
+
+fn default_effort() -> u32 {
+    2
+}
+
+#[derive(Serialize, Default)]
+struct ServeResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    partition_path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    connectivity: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feasible: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    elapsed: Option<f64>,
+}
+
+fn serve_error(message: impl Into<String>) -> ServeResponse {
+    ServeResponse {
+        ok: false,
+        error: Some(message.into()),
+        ..Default::default()
+    }
+}
+
+/// Loads the request's hypergraph, solving against `gpu_ctx` (lazily built
+/// on first use and kept resident for the rest of the session; it is not
+/// shared between TCP connections) unless the resolved device is `cpu`.
+/// Returns the response to write back.
+fn handle_request(
+    req: ServeRequest,
+    gpu_ctx: &mut Option<cuda::GpuContext>,
+    default_device: solver::Device,
+) -> ServeResponse {
+    match handle_request_inner(req, gpu_ctx, default_device) {
+        Ok(resp) => resp,
+        Err(e) => serve_error(e.to_string()),
+    }
+}
+
+fn handle_request_inner(
+    req: ServeRequest,
+    gpu_ctx: &mut Option<cuda::GpuContext>,
+    default_device: solver::Device,
+) -> Result<ServeResponse> {
+    let device: solver::Device = match &req.device {
+        Some(spec) => spec.parse()?,
+        None => default_device,
+    };
+
+    let (hgr_path, hypergraph) = match (&req.hgr_path, &req.hgr_inline) {
+        (Some(path), None) => (path.clone(), hgr::read_hgr(path)?),
+        (None, Some(inline)) => {
+            let tmp_path = std::env::temp_dir().join(format!("hg_bench_serve_{}.hgr", std::process::id()));
+            fs::write(&tmp_path, inline)?;
+            let hg = hgr::read_hgr(&tmp_path);
+            fs::remove_file(&tmp_path).ok();
+            (PathBuf::from("<inline>"), hg?)
+        }
+        _ => return Err(anyhow!("request must set exactly one of hgr_path or hgr_inline")),
+    };
+
+    let max_part_size = hgr::max_part_size(&hypergraph.node_weights, req.k, req.epsilon);
+
+    let start = Instant::now();
+    // Same `Auto`-falls-back-for-weighted-instances rule as `solver::solve`:
+    // an explicit `--device gpu` still hits the hard error in
+    // `hypergraph_to_challenge` below.
+    let use_gpu = solver::use_gpu(device)
+        && !(device == solver::Device::Auto && hgr::is_weighted(&hypergraph));
+    let partition = if use_gpu {
+        if gpu_ctx.is_none() {
+            *gpu_ctx = Some(cuda::GpuContext::for_device(0)?);
+        }
+        solver::solve_with_ctx(
+            &hypergraph,
+            req.k,
+            max_part_size,
+            req.effort,
+            req.refinement,
+            gpu_ctx.as_ref().unwrap(),
+        )?
+    } else {
+        cpu_solver::solve(&hypergraph, req.k, max_part_size, req.effort, req.refinement)?
+    };
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let partition_path = req
+        .out
+        .unwrap_or_else(|| hgr_path.with_extension("partition"));
+    hgr::write_partition(&partition_path, &partition)?;
+
+    let connectivity = hgr::compute_connectivity(&hypergraph, &partition);
+    let (feasible, _, _) = hgr::check_feasibility(&partition, &hypergraph.node_weights, req.k, max_part_size);
+
+    Ok(ServeResponse {
+        ok: true,
+        error: None,
+        partition_path: Some(partition_path),
+        connectivity: Some(connectivity),
+        feasible: Some(feasible),
+        elapsed: Some(elapsed),
+    })
+}
+
+/// Services one JSON-request-per-line, JSON-response-per-line session over
+/// the given streams, sharing one lazily-built `GpuContext` across requests.
+fn serve_session(
+    reader: impl std::io::BufRead,
+    mut writer: impl std::io::Write,
+    default_device: solver::Device,
+) -> Result<()> {
+    let mut gpu_ctx: Option<cuda::GpuContext> = None;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<ServeRequest>(&line) {
+            Ok(req) => handle_request(req, &mut gpu_ctx, default_device),
+            Err(e) => serve_error(format!("Invalid request: {}", e)),
+        };
+
+        writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+fn serve_stdio(default_device: solver::Device) -> Result<()> {
+    eprintln!("Serving requests on stdin/stdout (solve backend: {:?})", default_device);
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    serve_session(stdin.lock(), stdout.lock(), default_device)
+}
+
+fn serve_tcp(addr: &str, default_device: solver::Device) -> Result<()> {
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind(addr)?;
+    eprintln!("Listening on {} (solve backend: {:?})", addr, default_device);
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let peer = stream.peer_addr().ok();
+        eprintln!("Connection from {:?}", peer);
+
+        let reader = std::io::BufReader::new(stream.try_clone()?);
+        if let Err(e) = serve_session(reader, stream, default_device) {
+            eprintln!("Connection {:?} ended with error: {}", peer, e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_of_empty_is_default() {
+        let stats = Stats::compute(&[]);
+        assert_eq!(stats.min, 0.0);
+        assert_eq!(stats.max, 0.0);
+        assert_eq!(stats.mean, 0.0);
+    }
+
+    #[test]
+    fn stats_single_value() {
+        let stats = Stats::compute(&[7.0]);
+        assert_eq!(stats.min, 7.0);
+        assert_eq!(stats.median, 7.0);
+        assert_eq!(stats.p90, 7.0);
+        assert_eq!(stats.p99, 7.0);
+        assert_eq!(stats.max, 7.0);
+        assert_eq!(stats.mean, 7.0);
+        assert_eq!(stats.stddev, 0.0);
+    }
+
+    #[test]
+    fn stats_percentiles_on_sorted_input() {
+        // 0..=10, so each percentile's nearest-rank index (p/100 * 10) is a
+        // whole number and picks that exact value.
+        let values: Vec<f64> = (0..=10).map(|v| v as f64).collect();
+        let stats = Stats::compute(&values);
+        assert_eq!(stats.min, 0.0);
+        assert_eq!(stats.median, 5.0);
+        assert_eq!(stats.p90, 9.0);
+        assert_eq!(stats.max, 10.0);
+        assert_eq!(stats.mean, 5.0);
+    }
+
+    #[test]
+    fn stats_is_order_independent() {
+        let sorted = Stats::compute(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        let shuffled = Stats::compute(&[3.0, 1.0, 5.0, 2.0, 4.0]);
+        assert_eq!(sorted.min, shuffled.min);
+        assert_eq!(sorted.median, shuffled.median);
+        assert_eq!(sorted.max, shuffled.max);
+        assert_eq!(sorted.mean, shuffled.mean);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::cuda::GpuContext;
+use anyhow::{anyhow, Result};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// A pool of CUDA device ordinals that dispatches work items to whichever
+/// device's worker thread is free, instead of running a folder of instances
+/// strictly serially on device 0. The pool itself holds no `GpuContext`;
+/// each `run`/`run_from_receiver` call builds one per device on its worker
+/// threads.
+pub struct GpuPool {
+    devices: Vec<usize>,
+}
+
+impl GpuPool {
+    /// Record the devices in `devices`, or every visible device when
+    /// `devices` is `None`. No `GpuContext` is created here.
+    pub fn new(devices: Option<Vec<usize>>) -> Result<Self> {
+        let devices = match devices {
+            Some(d) => d,
+            None => (0..GpuContext::device_count()?).collect(),
+        };
+        if devices.is_empty() {
+            return Err(anyhow!("No CUDA devices available for the pool"));
+        }
+        Ok(Self { devices })
+    }
+
+    pub fn num_devices(&self) -> usize {
+        self.devices.len()
+    }
+
+    /// Run `work_fn` once per item in `items`, spread across one worker
+    /// thread per pool device. Each worker builds its device's `GpuContext`
+    /// once and keeps it for every item of this call, so PTX load and
+    /// device-prop queries happen once per GPU per call (a second call
+    /// builds fresh contexts). Results come back in the same order as
+    /// `items`, regardless of which device finished first.
+    pub fn run<T, R, F>(&self, items: Vec<T>, work_fn: F) -> Result<Vec<R>>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(&GpuContext, T) -> Result<R> + Send + Sync + 'static,
+    {
+        let total = items.len();
+        let (item_tx, item_rx) = mpsc::channel::<(usize, T)>();
+        for (index, item) in items.into_iter().enumerate() {
+            item_tx.send((index, item)).ok();
+        }
+        drop(item_tx);
+
+        self.run_from_receiver(item_rx, total, work_fn)
+    }
+
+    /// Same as [`run`], but pulls items from an externally fed channel
+    /// instead of an already-collected `Vec`. Lets a producer (e.g. a
+    /// bounded disk-prefetch pipeline) push items as they become ready
+    /// while GPU workers drain them concurrently, instead of requiring
+    /// every item to be loaded into memory up front.
+    pub fn run_from_receiver<T, R, F>(
+        &self,
+        item_rx: mpsc::Receiver<(usize, T)>,
+        total: usize,
+        work_fn: F,
+    ) -> Result<Vec<R>>
+    where
+        T: Send + 'static,
+        R: Send + 'static,
+        F: Fn(&GpuContext, T) -> Result<R> + Send + Sync + 'static,
+    {
+        let work_fn = Arc::new(work_fn);
+        let item_rx = Arc::new(Mutex::new(item_rx));
+        let (result_tx, result_rx) = mpsc::channel::<(usize, Result<R>)>();
+
+        let mut handles = Vec::with_capacity(self.devices.len());
+        for &device in &self.devices {
+            let item_rx = Arc::clone(&item_rx);
+            let result_tx = result_tx.clone();
+            let work_fn = Arc::clone(&work_fn);
+            handles.push(thread::spawn(move || -> Result<()> {
+                let ctx = GpuContext::for_device(device)?;
+                loop {
+                    let next = item_rx.lock().unwrap().recv();
+                    let (index, item) = match next {
+                        Ok(v) => v,
+                        Err(_) => break,
+                    };
+                    let result = work_fn(&ctx, item);
+                    if result_tx.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            }));
+        }
+        drop(result_tx);
+
+        let mut slots: Vec<Option<R>> = (0..total).map(|_| None).collect();
+        for _ in 0..total {
+            let (index, result) = result_rx
+                .recv()
+                .map_err(|_| anyhow!("GPU worker pool closed unexpectedly"))?;
+            slots[index] = Some(result?);
+        }
+
+        for handle in handles {
+            handle
+                .join()
+                .map_err(|_| anyhow!("GPU worker thread panicked"))??;
+        }
+
+        Ok(slots.into_iter().map(|s| s.unwrap()).collect())
+    }
+}
@@ -1,8 +1,9 @@
 use crate::cuda::GpuContext;
 use crate::hgr::Hypergraph;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use cudarc::driver::CudaSlice;
 use serde_json::{Map, Value};
+use std::str::FromStr;
 use tig_challenges::hypergraph::{Challenge, Solution};
 
 pub mod track_10k;
@@ -11,16 +12,72 @@ pub mod track_50k;
 pub mod track_100k;
 pub mod track_200k;
 
+/// Which backend `solve` should dispatch to. `Auto` prefers the GPU tracks
+/// but falls back to [`crate::cpu_solver`] when no CUDA device is visible,
+/// instead of failing outright.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Device {
+    Cpu,
+    Gpu,
+    Auto,
+}
+
+impl FromStr for Device {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "cpu" => Ok(Device::Cpu),
+            "gpu" => Ok(Device::Gpu),
+            "auto" => Ok(Device::Auto),
+            other => Err(anyhow!("Unknown --device '{}': expected cpu, gpu, or auto", other)),
+        }
+    }
+}
+
+/// Resolves `Auto` against the number of visible CUDA devices; `Cpu`/`Gpu`
+/// pass through unconditionally.
+pub fn use_gpu(device: Device) -> bool {
+    match device {
+        Device::Cpu => false,
+        Device::Gpu => true,
+        Device::Auto => GpuContext::device_count().unwrap_or(0) > 0,
+    }
+}
+
 pub fn solve(
     hypergraph: &Hypergraph,
     k: u32,
     max_part_size: u32,
     effort: u32,
     refinement: Option<u32>,
+    device: Device,
 ) -> Result<Vec<u32>> {
-    let ctx = GpuContext::new()?;
-    
-    let challenge = hypergraph_to_challenge(hypergraph, k, max_part_size, &ctx)?;
+    // `Auto` already falls back to the CPU backend when no CUDA device is
+    // visible; do the same for weighted instances, which the GPU backend
+    // can't optimize correctly (see `hypergraph_to_challenge`). `Device::Gpu`
+    // is an explicit request, so let it through to the hard error there
+    // instead of silently switching backends on the caller.
+    if !use_gpu(device) || (device == Device::Auto && crate::hgr::is_weighted(hypergraph)) {
+        return crate::cpu_solver::solve(hypergraph, k, max_part_size, effort, refinement);
+    }
+
+    let ctx = GpuContext::for_device(0)?;
+    solve_with_ctx(hypergraph, k, max_part_size, effort, refinement, &ctx)
+}
+
+/// Same as [`solve`], but runs against an already-constructed `GpuContext`
+/// instead of opening device 0. Lets callers that manage their own device
+/// pool (see `scheduler::GpuPool`) reuse one context across many solves.
+pub fn solve_with_ctx(
+    hypergraph: &Hypergraph,
+    k: u32,
+    max_part_size: u32,
+    effort: u32,
+    refinement: Option<u32>,
+    ctx: &GpuContext,
+) -> Result<Vec<u32>> {
+    let challenge = hypergraph_to_challenge(hypergraph, k, max_part_size, ctx)?;
     
     let mut hyperparameters: Map<String, Value> = Map::new();
     hyperparameters.insert("effort".to_string(), Value::Number(effort.into()));
@@ -54,25 +111,61 @@ fn hypergraph_to_challenge(
     max_part_size: u32,
     ctx: &GpuContext,
 ) -> Result<Challenge> {
+    // `tig_challenges::hypergraph::Challenge` has no weight fields to receive
+    // `hg.hyperedge_weights`/`hg.node_weights`, so the GPU tracks would
+    // optimize the unweighted connectivity metric instead of the weighted
+    // one a caller asked for -- reject rather than silently solve the wrong
+    // objective. `solver::solve`'s `Device::Auto` path already routes
+    // weighted instances to the CPU backend before reaching here; this is
+    // what turns an explicit `--device gpu` into a hard error instead.
+    if crate::hgr::is_weighted(hg) {
+        return Err(anyhow!(
+            "the GPU backend does not support weighted .hgr instances: `Challenge` has no \
+             weight fields, so the GPU tracks would optimize the unweighted connectivity metric \
+             instead of the weighted one. Use --device cpu (or auto, which falls back \
+             automatically) for weighted inputs."
+        ));
+    }
+
     let d_hyperedge_offsets: CudaSlice<i32> = ctx.stream.memcpy_stod(&hg.hyperedge_offsets)?;
     let d_hyperedge_nodes: CudaSlice<i32> = ctx.stream.memcpy_stod(&hg.hyperedge_nodes)?;
-    let d_node_offsets: CudaSlice<i32> = ctx.stream.memcpy_stod(&hg.node_offsets)?;
-    let d_node_hyperedges: CudaSlice<i32> = ctx.stream.memcpy_stod(&hg.node_hyperedges)?;
-    
+
+    // The node-to-hyperedge CSR is a transpose of what was just uploaded
+    // above, so with `gpu-csr-transpose` enabled we rebuild it on-device
+    // from those same slices instead of re-uploading `hg.node_offsets`/
+    // `hg.node_hyperedges` (which `read_hgr` already computed once on the
+    // CPU). `node_hyperedges` in particular can be large, so keeping it
+    // device-resident end to end avoids a host round trip for it entirely.
+    #[cfg(feature = "gpu-csr-transpose")]
+    let (node_offsets, d_node_offsets, d_node_hyperedges) = crate::hgr::build_node_to_hyperedge_gpu(
+        ctx,
+        hg.num_nodes as usize,
+        hg.num_hyperedges as usize,
+        hg.hyperedge_nodes.len(),
+        &d_hyperedge_offsets,
+        &d_hyperedge_nodes,
+    )?;
+    #[cfg(not(feature = "gpu-csr-transpose"))]
+    let (node_offsets, d_node_offsets, d_node_hyperedges): (Vec<i32>, CudaSlice<i32>, CudaSlice<i32>) = (
+        hg.node_offsets.clone(),
+        ctx.stream.memcpy_stod(&hg.node_offsets)?,
+        ctx.stream.memcpy_stod(&hg.node_hyperedges)?,
+    );
+
     let mut hyperedge_sizes: Vec<i32> = Vec::with_capacity(hg.num_hyperedges as usize);
     for i in 0..hg.num_hyperedges as usize {
         let size = hg.hyperedge_offsets[i + 1] - hg.hyperedge_offsets[i];
         hyperedge_sizes.push(size);
     }
     let d_hyperedge_sizes: CudaSlice<i32> = ctx.stream.memcpy_stod(&hyperedge_sizes)?;
-    
+
     let mut node_degrees: Vec<i32> = Vec::with_capacity(hg.num_nodes as usize);
     for i in 0..hg.num_nodes as usize {
-        let degree = hg.node_offsets[i + 1] - hg.node_offsets[i];
+        let degree = node_offsets[i + 1] - node_offsets[i];
         node_degrees.push(degree);
     }
     let d_node_degrees: CudaSlice<i32> = ctx.stream.memcpy_stod(&node_degrees)?;
-    
+
     let total_connections = hg.hyperedge_nodes.len() as u32;
     
     Ok(Challenge {